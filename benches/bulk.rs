@@ -0,0 +1,76 @@
+//! Bulk transfers and integer accessors compared to a plain `memcpy`
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+
+use bytes_ringbuffer::{Buf, BufMut, RingBuffer};
+
+const SIZES: [usize; 3] = [64, 4096, 64 * 1024];
+
+/// Empty ringbuffer whose next write starts `before_wrap` bytes before the end
+/// of the storage
+fn positioned(capacity: usize, before_wrap: usize) -> RingBuffer {
+    let mut buf = RingBuffer::new(capacity);
+    let offset = capacity - before_wrap;
+    buf.put_slice(&vec![0; offset]);
+    buf.advance(offset);
+    buf
+}
+
+fn bulk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk");
+    for &size in SIZES.iter() {
+        let src = vec![1; size];
+        let mut dst = vec![0; size];
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_function(BenchmarkId::new("memcpy", size), |b| {
+            let mut storage = vec![0; size];
+            b.iter(|| {
+                storage.copy_from_slice(black_box(&src));
+                dst.copy_from_slice(black_box(&storage));
+            })
+        });
+        for &(name, before_wrap) in [("contiguous", 2 * size), ("across_wrap", size / 2)].iter() {
+            group.bench_function(BenchmarkId::new(name, size), |b| {
+                b.iter_batched_ref(
+                    || positioned(2 * size, before_wrap),
+                    |buf| {
+                        buf.put_slice(black_box(&src));
+                        buf.copy_to_slice(black_box(&mut dst));
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+fn ints(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ints");
+    for &(name, before_wrap) in [("contiguous", 16), ("across_wrap", 2)].iter() {
+        group.bench_function(BenchmarkId::new("put_u64", name), |b| {
+            b.iter_batched_ref(
+                || positioned(16, before_wrap),
+                |buf| buf.put_u64(black_box(0x0102_0304_0506_0708)),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("get_u32", name), |b| {
+            b.iter_batched_ref(
+                || {
+                    let mut buf = positioned(16, before_wrap);
+                    buf.put_u32(0x0102_0304);
+                    buf
+                },
+                |buf| black_box(buf.get_u32()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bulk, ints);
+criterion_main!(benches);
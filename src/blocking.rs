@@ -0,0 +1,317 @@
+//! Thread-safe ringbuffer that blocks producers while it is full and consumers
+//! while it is empty
+use core::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::{Buf, BufMut, CapacityError, DefaultStorage, Mode, RingBuffer, Storage};
+
+/// Error returned by the operations of a
+/// [BlockingRingBuffer](struct.BlockingRingBuffer.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockingError {
+    /// The buffer has been closed
+    Closed,
+    /// The timeout elapsed before the operation could proceed
+    TimedOut,
+    /// The operation would have to block
+    WouldBlock,
+    /// The slice can never fit into the buffer
+    Capacity(CapacityError),
+}
+
+impl fmt::Display for BlockingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockingError::Closed => write!(f, "ringbuffer is closed"),
+            BlockingError::TimedOut => write!(f, "timed out"),
+            BlockingError::WouldBlock => write!(f, "operation would block"),
+            BlockingError::Capacity(err) => write!(f, "slice does not fit: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BlockingError {}
+
+impl From<CapacityError> for BlockingError {
+    fn from(err: CapacityError) -> Self {
+        BlockingError::Capacity(err)
+    }
+}
+
+/// Ringbuffer that can be shared between any number of producer and consumer
+/// threads, e.g. in an `Arc`
+///
+/// Producers block while the slice they put does not fit and consumers block
+/// while nothing is readable. Buffers in overwrite or growable mode never block
+/// producers. After [close](#method.close) the remaining bytes can still be
+/// read, but nothing can be written.
+#[derive(Debug)]
+pub struct BlockingRingBuffer<S = DefaultStorage> {
+    state: Mutex<State<S>>,
+    readable: Condvar,
+    writable: Condvar,
+}
+
+#[derive(Debug)]
+struct State<S> {
+    buf: RingBuffer<S>,
+    closed: bool,
+}
+
+/// How long an operation waits for the buffer
+#[derive(Clone, Copy)]
+enum Wait {
+    Forever,
+    Timeout(Duration),
+    Never,
+}
+
+impl BlockingRingBuffer {
+    /// Create a blocking ringbuffer with the given capacity
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer::new(capacity).into()
+    }
+}
+
+impl<S: Storage> From<RingBuffer<S>> for BlockingRingBuffer<S> {
+    fn from(buf: RingBuffer<S>) -> Self {
+        Self {
+            state: Mutex::new(State { buf, closed: false }),
+            readable: Condvar::new(),
+            writable: Condvar::new(),
+        }
+    }
+}
+
+impl<S: Storage> BlockingRingBuffer<S> {
+    /// Capacity of the ringbuffer
+    pub fn capacity(&self) -> usize {
+        self.lock().buf.capacity()
+    }
+
+    /// Number of readable bytes
+    pub fn len(&self) -> usize {
+        self.lock().buf.remaining()
+    }
+
+    /// Whether nothing is readable
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Close the buffer and wake up all blocked producers and consumers
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.readable.notify_all();
+        self.writable.notify_all();
+    }
+
+    /// Whether the buffer has been closed
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Return the inner ringbuffer
+    pub fn into_inner(self) -> RingBuffer<S> {
+        self.state
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+            .buf
+    }
+
+    /// Write all of `src`, blocking until it fits
+    ///
+    /// The slice is written at once, so slices of concurrent producers never
+    /// interleave. Returns an error if the buffer is closed or if `src` is
+    /// larger than the capacity.
+    pub fn put_slice(&self, src: &[u8]) -> Result<(), BlockingError> {
+        self.put(src, Wait::Forever)
+    }
+
+    /// Like [put_slice](#method.put_slice), but gives up after `timeout`
+    pub fn put_slice_timeout(&self, src: &[u8], timeout: Duration) -> Result<(), BlockingError> {
+        self.put(src, Wait::Timeout(timeout))
+    }
+
+    /// Like [put_slice](#method.put_slice), but returns
+    /// `BlockingError::WouldBlock` instead of blocking
+    pub fn try_put_slice(&self, src: &[u8]) -> Result<(), BlockingError> {
+        self.put(src, Wait::Never)
+    }
+
+    /// Read up to `dst.len()` bytes, blocking until at least one is readable
+    ///
+    /// Returns the number of bytes read. Once the buffer is closed the
+    /// remaining bytes are still returned before it fails with
+    /// `BlockingError::Closed`.
+    pub fn read(&self, dst: &mut [u8]) -> Result<usize, BlockingError> {
+        self.get(dst, Wait::Forever)
+    }
+
+    /// Like [read](#method.read), but gives up after `timeout`
+    pub fn read_timeout(&self, dst: &mut [u8], timeout: Duration) -> Result<usize, BlockingError> {
+        self.get(dst, Wait::Timeout(timeout))
+    }
+
+    /// Like [read](#method.read), but returns `BlockingError::WouldBlock`
+    /// instead of blocking
+    pub fn try_read(&self, dst: &mut [u8]) -> Result<usize, BlockingError> {
+        self.get(dst, Wait::Never)
+    }
+
+    fn put(&self, src: &[u8], wait: Wait) -> Result<(), BlockingError> {
+        let mut state = self.lock();
+        if state.buf.mode == Mode::Fixed && src.len() > state.buf.capacity() {
+            return Err(CapacityError::new(src.len(), state.buf.capacity()).into());
+        }
+        state = self.wait(&self.writable, state, wait, |buf| {
+            buf.remaining_mut() < src.len()
+        })?;
+        if state.closed {
+            return Err(BlockingError::Closed);
+        }
+        state.buf.put_slice(src);
+        drop(state);
+        self.readable.notify_all();
+        Ok(())
+    }
+
+    fn get(&self, dst: &mut [u8], wait: Wait) -> Result<usize, BlockingError> {
+        if dst.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.lock();
+        state = self.wait(&self.readable, state, wait, |buf| !buf.has_remaining())?;
+        if !state.buf.has_remaining() {
+            return Err(BlockingError::Closed);
+        }
+        let cnt = dst.len().min(state.buf.remaining());
+        state.buf.copy_to_slice(&mut dst[..cnt]);
+        drop(state);
+        self.writable.notify_all();
+        Ok(cnt)
+    }
+
+    /// Wait on `condvar` until the buffer is closed or no longer `blocked`
+    fn wait<'a, F>(
+        &self,
+        condvar: &Condvar,
+        mut state: MutexGuard<'a, State<S>>,
+        wait: Wait,
+        mut blocked: F,
+    ) -> Result<MutexGuard<'a, State<S>>, BlockingError>
+    where
+        F: FnMut(&RingBuffer<S>) -> bool,
+    {
+        let mut waiting = |state: &mut State<S>| !state.closed && blocked(&state.buf);
+        match wait {
+            Wait::Forever => Ok(condvar
+                .wait_while(state, &mut waiting)
+                .unwrap_or_else(|err| err.into_inner())),
+            Wait::Timeout(timeout) => {
+                let (state, result) = condvar
+                    .wait_timeout_while(state, timeout, &mut waiting)
+                    .unwrap_or_else(|err| err.into_inner());
+                if result.timed_out() {
+                    Err(BlockingError::TimedOut)
+                } else {
+                    Ok(state)
+                }
+            }
+            Wait::Never if waiting(&mut state) => Err(BlockingError::WouldBlock),
+            Wait::Never => Ok(state),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<S>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn blocking_ringbuffer_threads() {
+        let buf = Arc::new(BlockingRingBuffer::new(16));
+        let producers: Vec<_> = (0..4u8)
+            .map(|i| {
+                let buf = buf.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        buf.put_slice(&[i; 4]).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..2)
+            .map(|_| {
+                let buf = buf.clone();
+                thread::spawn(move || {
+                    let mut sum = 0u64;
+                    let mut dst = [0; 7];
+                    while let Ok(cnt) = buf.read(&mut dst) {
+                        sum += dst[..cnt].iter().map(|b| u64::from(*b)).sum::<u64>();
+                    }
+                    sum
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        buf.close();
+        let sum: u64 = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+        assert_eq!(sum, (1 + 2 + 3) * 4 * 1000);
+    }
+
+    #[test]
+    fn blocking_ringbuffer_try_timeout() {
+        let buf = BlockingRingBuffer::new(4);
+        let mut dst = [0; 4];
+        assert_eq!(buf.try_read(&mut dst), Err(BlockingError::WouldBlock));
+        let timeout = Duration::from_millis(10);
+        assert_eq!(
+            buf.read_timeout(&mut dst, timeout),
+            Err(BlockingError::TimedOut)
+        );
+
+        buf.try_put_slice(&[1, 2, 3]).unwrap();
+        assert_eq!(buf.try_put_slice(&[4, 5]), Err(BlockingError::WouldBlock));
+        assert_eq!(
+            buf.put_slice_timeout(&[4, 5], timeout),
+            Err(BlockingError::TimedOut)
+        );
+        assert_eq!(
+            buf.put_slice(&[0; 5]),
+            Err(BlockingError::Capacity(CapacityError::new(5, 4)))
+        );
+        assert_eq!(buf.try_read(&mut dst), Ok(3));
+        assert_eq!(dst[..3], [1, 2, 3]);
+    }
+
+    #[test]
+    fn blocking_ringbuffer_close() {
+        let buf = Arc::new(BlockingRingBuffer::new(4));
+        buf.put_slice(&[1, 2, 3]).unwrap();
+        let producer = {
+            let buf = buf.clone();
+            thread::spawn(move || buf.put_slice(&[4, 5]))
+        };
+        // Give the producer time to block
+        thread::sleep(Duration::from_millis(10));
+        buf.close();
+        assert_eq!(producer.join().unwrap(), Err(BlockingError::Closed));
+        assert!(buf.is_closed());
+
+        // Remaining bytes are still readable
+        let mut dst = [0; 4];
+        assert_eq!(buf.read(&mut dst), Ok(3));
+        assert_eq!(buf.read(&mut dst), Err(BlockingError::Closed));
+        assert_eq!(buf.try_put_slice(&[1]), Err(BlockingError::Closed));
+    }
+}
@@ -0,0 +1,312 @@
+//! Ringbuffer whose byte stream is read by several independent readers
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{Buf, BufMut, RingBuffer};
+
+/// Handle of a reader of a [BroadcastRingBuffer](struct.BroadcastRingBuffer.html)
+///
+/// Every subscription gets a new id, so the id of a removed reader is never
+/// valid again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderId {
+    index: usize,
+    serial: u64,
+}
+
+/// Position of a reader and the serial number of its subscription
+#[derive(Debug)]
+struct Reader {
+    serial: u64,
+    cursor: u64,
+}
+
+/// Error returned if the bytes a reader was about to read have been
+/// overwritten
+///
+/// The reader continues with the oldest byte that is still buffered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaggedError {
+    missed: u64,
+}
+
+impl LaggedError {
+    /// Number of bytes the reader has missed
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+impl fmt::Display for LaggedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "reader lagged by {} bytes", self.missed)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LaggedError {}
+
+/// Ringbuffer that delivers every written byte to each of its readers
+///
+/// Every reader has its own cursor and only sees the bytes written after it
+/// [subscribed](#method.subscribe). Created by [new](#method.new) the writer
+/// waits for the slowest reader, i.e. it can only write as many bytes as that
+/// reader has made room for. Created by [with_overwrite](#method.with_overwrite)
+/// the writer overwrites bytes that slow readers have not read yet and these
+/// readers get a [LaggedError](struct.LaggedError.html). Bytes written while
+/// there are no readers are discarded.
+#[derive(Debug)]
+pub struct BroadcastRingBuffer {
+    /// Bytes from the slowest reader up to the writer
+    buf: RingBuffer,
+    /// Stream position of the next written byte
+    head: u64,
+    /// Readers, indexed by `ReaderId`
+    readers: Vec<Option<Reader>>,
+    /// Serial number of the next subscription
+    serial: u64,
+}
+
+impl BroadcastRingBuffer {
+    /// Create a broadcast ringbuffer whose writer waits for the slowest reader
+    pub fn new(capacity: usize) -> Self {
+        Self::with_buf(RingBuffer::new(capacity))
+    }
+
+    /// Create a broadcast ringbuffer whose writer overwrites bytes that have
+    /// not been read by all readers
+    pub fn with_overwrite(capacity: usize) -> Self {
+        Self::with_buf(RingBuffer::with_overwrite(capacity))
+    }
+
+    fn with_buf(buf: RingBuffer) -> Self {
+        Self {
+            buf,
+            head: 0,
+            readers: Vec::new(),
+            serial: 0,
+        }
+    }
+
+    /// Capacity of the ringbuffer
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Whether the writer overwrites unread bytes
+    pub fn is_overwrite(&self) -> bool {
+        self.buf.is_overwrite()
+    }
+
+    /// Add a reader that starts at the next written byte
+    pub fn subscribe(&mut self) -> ReaderId {
+        let serial = self.serial;
+        self.serial += 1;
+        let reader = Some(Reader {
+            serial,
+            cursor: self.head,
+        });
+        let index = match self.readers.iter().position(Option::is_none) {
+            Some(index) => {
+                self.readers[index] = reader;
+                index
+            }
+            None => {
+                self.readers.push(reader);
+                self.readers.len() - 1
+            }
+        };
+        ReaderId { index, serial }
+    }
+
+    /// Remove a reader and free the bytes only it had left to read
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reader` has already been removed.
+    pub fn unsubscribe(&mut self, reader: ReaderId) {
+        self.cursor(reader);
+        self.readers[reader.index] = None;
+        while let Some(None) = self.readers.last() {
+            self.readers.pop();
+        }
+        self.trim();
+    }
+
+    /// Number of readers
+    pub fn reader_count(&self) -> usize {
+        self.readers.iter().flatten().count()
+    }
+
+    /// Number of bytes that can be written without overwriting unread bytes
+    pub fn remaining_mut(&self) -> usize {
+        self.capacity() - self.buf.remaining()
+    }
+
+    /// Number of bytes `reader` can read
+    ///
+    /// If the reader has lagged behind only the bytes that are still buffered
+    /// are counted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reader` has been removed.
+    pub fn remaining(&self, reader: ReaderId) -> usize {
+        (self.head - self.cursor(reader).max(self.tail())) as usize
+    }
+
+    /// Write bytes from `src` and return how many were written
+    ///
+    /// Without overwrite at most [remaining_mut](#method.remaining_mut) bytes
+    /// are written. With overwrite all of `src` is written.
+    pub fn write(&mut self, src: &[u8]) -> usize {
+        let cnt = if self.is_overwrite() {
+            src.len()
+        } else {
+            src.len().min(self.remaining_mut())
+        };
+        self.buf.put_slice(&src[..cnt]);
+        self.head += cnt as u64;
+        if self.readers.is_empty() {
+            self.trim();
+        }
+        cnt
+    }
+
+    /// Copy the next bytes of `reader` into `dst` and return how many were
+    /// copied
+    ///
+    /// Returns an error without copying anything if bytes have been overwritten
+    /// before `reader` read them. The next read continues with the oldest
+    /// buffered byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reader` has been removed.
+    pub fn read(&mut self, reader: ReaderId, dst: &mut [u8]) -> Result<usize, LaggedError> {
+        let cursor = self.cursor(reader);
+        let tail = self.tail();
+        if cursor < tail {
+            self.set_cursor(reader, tail);
+            return Err(LaggedError {
+                missed: tail - cursor,
+            });
+        }
+        let cnt = self.buf.peek((cursor - tail) as usize, dst);
+        self.set_cursor(reader, cursor + cnt as u64);
+        self.trim();
+        Ok(cnt)
+    }
+
+    /// Stream position of the oldest buffered byte
+    fn tail(&self) -> u64 {
+        self.head - self.buf.remaining() as u64
+    }
+
+    fn cursor(&self, reader: ReaderId) -> u64 {
+        match self.readers.get(reader.index) {
+            Some(Some(slot)) if slot.serial == reader.serial => slot.cursor,
+            _ => panic!("reader {} has been removed", reader.serial),
+        }
+    }
+
+    fn set_cursor(&mut self, reader: ReaderId, cursor: u64) {
+        if let Some(Some(slot)) = self.readers.get_mut(reader.index) {
+            slot.cursor = cursor;
+        }
+    }
+
+    /// Drop the bytes that every reader has read
+    fn trim(&mut self) {
+        let slowest = self.readers.iter().flatten().map(|r| r.cursor).min();
+        let tail = self.tail();
+        let read = slowest.unwrap_or(self.head).saturating_sub(tail);
+        self.buf.advance(read as usize);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn broadcast_ringbuffer_slowest_reader() {
+        let mut buf = BroadcastRingBuffer::new(4);
+        assert_eq!(buf.write(&[0; 4]), 4);
+        assert_eq!(buf.remaining_mut(), 4);
+
+        let fast = buf.subscribe();
+        let slow = buf.subscribe();
+        assert_eq!(buf.write(&[1, 2, 3, 4, 5]), 4);
+        let mut dst = [0; 8];
+        assert_eq!(buf.read(fast, &mut dst), Ok(4));
+        assert_eq!(dst[..4], [1, 2, 3, 4]);
+        assert_eq!(buf.write(&[5]), 0);
+
+        // The writer waits for the slow reader
+        assert_eq!(buf.read(slow, &mut dst[..3]), Ok(3));
+        assert_eq!(buf.remaining_mut(), 3);
+        assert_eq!(buf.write(&[5, 6, 7, 8]), 3);
+        assert_eq!(buf.read(fast, &mut dst), Ok(3));
+        assert_eq!(dst[..3], [5, 6, 7]);
+        assert_eq!(buf.remaining(slow), 4);
+        assert_eq!(buf.read(slow, &mut dst), Ok(4));
+        assert_eq!(dst[..4], [4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn broadcast_ringbuffer_lagged() {
+        let mut buf = BroadcastRingBuffer::with_overwrite(4);
+        let fast = buf.subscribe();
+        let slow = buf.subscribe();
+        let mut dst = [0; 8];
+        for chunk in [[1, 2, 3], [4, 5, 6]].iter() {
+            assert_eq!(buf.write(chunk), 3);
+            assert_eq!(buf.read(fast, &mut dst), Ok(3));
+            assert_eq!(dst[..3], chunk[..]);
+        }
+
+        let err = buf.read(slow, &mut dst).unwrap_err();
+        assert_eq!(err.missed(), 2);
+        assert_eq!(err.to_string(), "reader lagged by 2 bytes");
+        assert_eq!(buf.read(slow, &mut dst), Ok(4));
+        assert_eq!(dst[..4], [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn broadcast_ringbuffer_unsubscribe() {
+        let mut buf = BroadcastRingBuffer::new(4);
+        let first = buf.subscribe();
+        let second = buf.subscribe();
+        buf.write(&[1, 2, 3, 4]);
+        let mut dst = [0; 4];
+        assert_eq!(buf.read(first, &mut dst[..2]), Ok(2));
+
+        // Removing the slowest reader frees the bytes it had left
+        buf.unsubscribe(second);
+        assert_eq!(buf.reader_count(), 1);
+        assert_eq!(buf.remaining_mut(), 2);
+
+        // A new reader only sees the bytes written after it subscribed
+        let third = buf.subscribe();
+        assert_ne!(third, second);
+        buf.write(&[5, 6]);
+        assert_eq!(buf.read(third, &mut dst), Ok(2));
+        assert_eq!(dst[..2], [5, 6]);
+        assert_eq!(buf.read(first, &mut dst), Ok(4));
+        assert_eq!(dst, [3, 4, 5, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "has been removed")]
+    fn broadcast_ringbuffer_stale_reader() {
+        let mut buf = BroadcastRingBuffer::new(4);
+        let first = buf.subscribe();
+        buf.unsubscribe(first);
+        // The new reader takes over the slot of the removed one
+        let _second = buf.subscribe();
+        buf.write(&[1]);
+        buf.read(first, &mut [0; 1]).unwrap();
+    }
+}
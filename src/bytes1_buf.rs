@@ -0,0 +1,129 @@
+//! Implementations of the `Buf` and `BufMut` traits of bytes 1.x
+//!
+//! They forward to the bytes 0.5 implementations, so both versions behave the
+//! same. Bring the traits of the version you use into scope, not both.
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+use bytes1::buf::UninitSlice;
+
+#[cfg(all(target_os = "linux", feature = "std"))]
+use super::MirroredRingBuffer;
+#[cfg(feature = "alloc")]
+use super::{Consumer, Producer, SharedRingBuffer};
+use super::{RingBuffer, Storage};
+
+/// Chunk of free space in the form bytes 1.x expects
+fn uninit(slice: &mut [MaybeUninit<u8>]) -> &mut UninitSlice {
+    UninitSlice::uninit(slice)
+}
+
+macro_rules! forward_buf {
+    ($([$($generics:tt)*] $ty:ty;)*) => {
+        $(
+            impl<$($generics)*> bytes1::Buf for $ty {
+                fn remaining(&self) -> usize {
+                    bytes::Buf::remaining(self)
+                }
+
+                fn chunk(&self) -> &[u8] {
+                    bytes::Buf::bytes(self)
+                }
+
+                #[cfg(feature = "std")]
+                fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+                    bytes::Buf::bytes_vectored(self, dst)
+                }
+
+                fn advance(&mut self, cnt: usize) {
+                    bytes::Buf::advance(self, cnt)
+                }
+
+                fn copy_to_slice(&mut self, dst: &mut [u8]) {
+                    bytes::Buf::copy_to_slice(self, dst)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! forward_buf_mut {
+    ($([$($generics:tt)*] $ty:ty;)*) => {
+        $(
+            unsafe impl<$($generics)*> bytes1::BufMut for $ty {
+                fn remaining_mut(&self) -> usize {
+                    bytes::BufMut::remaining_mut(self)
+                }
+
+                unsafe fn advance_mut(&mut self, cnt: usize) {
+                    bytes::BufMut::advance_mut(self, cnt)
+                }
+
+                fn chunk_mut(&mut self) -> &mut UninitSlice {
+                    uninit(bytes::BufMut::bytes_mut(self))
+                }
+
+                fn put_slice(&mut self, src: &[u8]) {
+                    bytes::BufMut::put_slice(self, src)
+                }
+            }
+        )*
+    };
+}
+
+forward_buf! {
+    [S: Storage] RingBuffer<S>;
+}
+
+forward_buf_mut! {
+    [S: Storage] RingBuffer<S>;
+}
+
+#[cfg(feature = "alloc")]
+forward_buf! {
+    [] Consumer;
+    [] SharedRingBuffer;
+}
+
+#[cfg(feature = "alloc")]
+forward_buf_mut! {
+    [] Producer;
+    [] SharedRingBuffer;
+}
+
+#[cfg(all(target_os = "linux", feature = "std"))]
+forward_buf! {
+    [] MirroredRingBuffer;
+}
+
+#[cfg(all(target_os = "linux", feature = "std"))]
+forward_buf_mut! {
+    [] MirroredRingBuffer;
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{wrapped, RingBuffer};
+    use bytes1::{Buf, BufMut};
+
+    #[test]
+    fn ringbuffer_bytes1() {
+        let mut buf = wrapped(RingBuffer::new(8), 2, &[]);
+        buf.put_u32(0x0102_0304);
+        assert_eq!(buf.chunk(), &[1, 2]);
+        assert_eq!(buf.chunk_mut().len(), 4);
+        assert_eq!(buf.get_u32(), 0x0102_0304);
+
+        // Interoperates with bytes 1.x types
+        buf.put(bytes1::Bytes::from_static(b"abc"));
+        assert_eq!(&buf.copy_to_bytes(3)[..], b"abc");
+    }
+
+    #[test]
+    fn split_bytes1() {
+        let (mut producer, mut consumer) = RingBuffer::new(4).split();
+        producer.put_u16(1234);
+        assert_eq!(consumer.get_u16(), 1234);
+    }
+}
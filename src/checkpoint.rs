@@ -0,0 +1,387 @@
+//! Checkpoints that allow undoing reads and writes
+use super::{CapacityError, RingBuffer, Storage};
+
+/// Position of the reader of a ringbuffer returned by
+/// [checkpoint](struct.RingBuffer.html#method.checkpoint)
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "a checkpoint should be committed or rolled back"]
+pub struct ReadCheckpoint {
+    read: u64,
+    consumed: u64,
+    rearranged: u64,
+    begin: usize,
+    dropped: u64,
+    capacity: usize,
+}
+
+/// Position of the writer of a ringbuffer returned by
+/// [write_checkpoint](struct.RingBuffer.html#method.write_checkpoint)
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "a checkpoint should be committed or rolled back"]
+pub struct WriteCheckpoint {
+    pushed: u64,
+}
+
+impl<S: Storage> RingBuffer<S> {
+    /// Remember the current read position so that the following reads can be
+    /// undone by [rollback](#method.rollback)
+    pub fn checkpoint(&self) -> ReadCheckpoint {
+        ReadCheckpoint {
+            read: self.read,
+            consumed: self.consumed(),
+            rearranged: self.rearranged,
+            begin: self.begin,
+            dropped: self.dropped,
+            capacity: self.capacity(),
+        }
+    }
+
+    /// Make the bytes consumed since `checkpoint` readable again
+    ///
+    /// Consumed bytes are not erased, but their space can be reused by later
+    /// writes. Returns an error without changing anything if some of the bytes
+    /// have been overwritten since, if bytes have been evicted in overwrite mode
+    /// or if the storage has grown or been rearranged.
+    pub fn rollback(&mut self, checkpoint: ReadCheckpoint) -> Result<(), CapacityError> {
+        let consumed = self.consumed().wrapping_sub(checkpoint.consumed) as usize;
+        if consumed == 0 {
+            // Nothing was read, so there is nothing to undo even if padding has
+            // been skipped or moved away
+            return Ok(());
+        }
+        let cnt = self.read.wrapping_sub(checkpoint.read);
+        let capacity = self.capacity() as u64;
+        let moved = checkpoint.rearranged != self.rearranged
+            || checkpoint.dropped != self.dropped
+            || checkpoint.capacity != self.capacity()
+            || cnt > capacity
+            || self.wrap(self.begin + self.capacity() - cnt as usize) != checkpoint.begin;
+        if moved {
+            return Err(CapacityError::new(consumed, 0));
+        }
+        // A consumed byte is overwritten once a write reaches one capacity past
+        // it
+        let lost = self
+            .written
+            .saturating_sub(checkpoint.read + capacity)
+            .min(cnt);
+        if lost > 0 {
+            return Err(CapacityError::new(cnt as usize, (cnt - lost) as usize));
+        }
+        // Consuming the bytes may have skipped a padding at the end
+        let padding = if checkpoint.begin + cnt as usize >= self.capacity() {
+            self.last_padding
+        } else {
+            self.padding
+        };
+        self.len = self.len + self.padding + cnt as usize - padding;
+        self.begin = checkpoint.begin;
+        self.padding = padding;
+        self.read = checkpoint.read;
+        Ok(())
+    }
+
+    /// Keep the reads made since `checkpoint`
+    pub fn commit(&mut self, checkpoint: ReadCheckpoint) {
+        let _ = checkpoint;
+    }
+
+    /// Remember the current write position so that the following writes can be
+    /// discarded by [rollback_write](#method.rollback_write)
+    pub fn write_checkpoint(&self) -> WriteCheckpoint {
+        WriteCheckpoint {
+            pushed: self.pushed(),
+        }
+    }
+
+    /// Discard the bytes written since `checkpoint`
+    ///
+    /// Returns an error without changing anything if some of the bytes have
+    /// already been consumed, dropped or evicted.
+    pub fn rollback_write(&mut self, checkpoint: WriteCheckpoint) -> Result<(), CapacityError> {
+        let cnt = self.pushed().wrapping_sub(checkpoint.pushed);
+        if cnt > self.len as u64 {
+            return Err(CapacityError::new(cnt as usize, self.len));
+        }
+        let wrapped = (self.begin + self.len + self.padding).saturating_sub(self.capacity());
+        if self.padding > 0 && cnt >= wrapped as u64 {
+            // The padding was skipped after the checkpoint and goes away too
+            self.skipped = self.skipped.wrapping_sub(self.padding as u64);
+            self.padding = 0;
+        }
+        self.len -= cnt as usize;
+        Ok(())
+    }
+
+    /// Keep the writes made since `checkpoint`
+    pub fn commit_write(&mut self, checkpoint: WriteCheckpoint) {
+        let _ = checkpoint;
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{wrapped, Buf, BufMut};
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn ringbuffer_rollback() {
+        let mut buf = wrapped(RingBuffer::new(8), 2, &[1, 2, 3, 4]);
+
+        // Undo reads across the wrap
+        let checkpoint = buf.checkpoint();
+        assert_eq!(buf.get_u16(), 0x0102);
+        assert_eq!(buf.get_u8(), 3);
+        buf.rollback(checkpoint).unwrap();
+        assert_eq!(buf.as_slices(), (&[1, 2][..], &[3, 4][..]));
+
+        // Writing into the free space keeps the consumed bytes intact
+        let checkpoint = buf.checkpoint();
+        buf.advance(2);
+        buf.put_slice(&[5, 6, 7, 8]);
+        buf.rollback(checkpoint).unwrap();
+        assert_eq!(buf.remaining(), 8);
+
+        // Until the space of the consumed bytes is written to
+        let checkpoint = buf.checkpoint();
+        buf.advance(3);
+        buf.put_u8(9);
+        assert_eq!(buf.rollback(checkpoint), Err(CapacityError::new(3, 2)));
+        assert_eq!(buf.remaining(), 6);
+
+        let checkpoint = buf.checkpoint();
+        assert_eq!(buf.get_u8(), 4);
+        buf.commit(checkpoint);
+        assert_eq!(buf.remaining(), 5);
+    }
+
+    #[test]
+    fn ringbuffer_rollback_moved() {
+        // Evicting bytes without reading any keeps the buffer as it is
+        let mut buf = RingBuffer::with_overwrite(4);
+        buf.put_slice(&[1, 2, 3]);
+        let checkpoint = buf.checkpoint();
+        buf.put_slice(&[4, 5]);
+        buf.rollback(checkpoint).unwrap();
+        assert_eq!(buf.as_slices(), (&[2, 3, 4][..], &[5][..]));
+
+        // So does rearranging the storage
+        let mut buf = wrapped(RingBuffer::new(4), 1, &[1, 2, 3]);
+        let checkpoint = buf.checkpoint();
+        buf.make_contiguous();
+        buf.rollback(checkpoint).unwrap();
+        assert_eq!(buf.as_slices(), (&[1, 2, 3][..], &[][..]));
+
+        // But reads before the rearrangement cannot be undone
+        let checkpoint = buf.checkpoint();
+        buf.advance(1);
+        buf.make_contiguous();
+        assert_eq!(buf.rollback(checkpoint), Err(CapacityError::new(1, 0)));
+        assert_eq!(buf.remaining(), 2);
+
+        // Moving the bytes in front of a padding does not read them
+        let mut buf = wrapped(RingBuffer::new(8), 4, &[5, 6]);
+        buf.grant_exact(3).unwrap().put_slice(&[7, 8, 9]);
+        let checkpoint = buf.checkpoint();
+        buf.try_reserve(1).unwrap();
+        buf.rollback(checkpoint).unwrap();
+        assert_eq!(buf.as_slices(), (&[5, 6][..], &[7, 8, 9][..]));
+
+        let mut buf = wrapped(RingBuffer::with_overwrite(8), 4, &[5, 6]);
+        buf.grant_exact(3).unwrap().put_slice(&[7, 8, 9]);
+        let checkpoint = buf.checkpoint();
+        buf.put_u8(10);
+        buf.rollback(checkpoint).unwrap();
+        assert_eq!(buf.as_slices(), (&[5, 6][..], &[7, 8, 9, 10][..]));
+    }
+
+    #[test]
+    fn ringbuffer_rollback_write() {
+        let mut buf = wrapped(RingBuffer::new(8), 4, &[0, 0]);
+
+        let checkpoint = buf.write_checkpoint();
+        buf.put_u32(0x0102_0304);
+        buf.rollback_write(checkpoint).unwrap();
+        assert_eq!(buf.remaining(), 2);
+
+        // Discarded bytes still overwrite the space of consumed bytes
+        let read = buf.checkpoint();
+        buf.advance(2);
+        let checkpoint = buf.write_checkpoint();
+        buf.put_slice(&[0; 8]);
+        buf.rollback_write(checkpoint).unwrap();
+        assert_eq!(buf.rollback(read), Err(CapacityError::new(2, 0)));
+
+        let checkpoint = buf.write_checkpoint();
+        buf.put_slice(&[1, 2, 3]);
+        buf.advance(1);
+        assert_eq!(
+            buf.rollback_write(checkpoint),
+            Err(CapacityError::new(3, 2))
+        );
+        let checkpoint = buf.write_checkpoint();
+        buf.put_u8(4);
+        buf.commit_write(checkpoint);
+        assert_eq!(buf.as_slices(), (&[2][..], &[3, 4][..]));
+    }
+
+    #[test]
+    fn ringbuffer_rollback_write_padding() {
+        // Growing moves the padding of a grant into the read position
+        let mut buf = wrapped(RingBuffer::growable(8), 2, &[1]);
+        let checkpoint = buf.write_checkpoint();
+        buf.grant_exact(3).unwrap().put_slice(&[2, 3, 4]);
+        buf.put_slice(&[5; 5]);
+        buf.rollback_write(checkpoint).unwrap();
+        assert_eq!(buf.as_slices(), (&[1][..], &[][..]));
+
+        // A grant into an empty buffer skips the end right away
+        let mut buf = wrapped(RingBuffer::new(8), 2, &[]);
+        let checkpoint = buf.write_checkpoint();
+        buf.grant_exact(4).unwrap().put_slice(&[1, 2, 3, 4]);
+        buf.rollback_write(checkpoint).unwrap();
+        assert_eq!(buf.remaining(), 0);
+    }
+
+    #[test]
+    fn ringbuffer_rollback_model() {
+        for seed in 1..1000u64 {
+            let mut rng = seed;
+            let mut next = |n: u64| {
+                // xorshift64
+                rng ^= rng << 13;
+                rng ^= rng >> 7;
+                rng ^= rng << 17;
+                (rng % n) as usize
+            };
+            let mut buf = match seed % 3 {
+                0 => RingBuffer::new(16),
+                1 => RingBuffer::growable(8),
+                _ => RingBuffer::with_overwrite(16),
+            };
+            let mut model = VecDeque::<u8>::new();
+            let mut checkpoint = None;
+            for i in 0..50u8 {
+                match next(7) {
+                    0 => {
+                        let n = next(8);
+                        let src: Vec<u8> = (0..n as u8).map(|b| i ^ b).collect();
+                        if buf.try_put_slice(&src).is_ok() {
+                            model.extend(&src);
+                            model.drain(..model.len().saturating_sub(buf.capacity()));
+                        }
+                    }
+                    1 => {
+                        let n = next(8);
+                        if let Ok(mut grant) = buf.grant_exact(n) {
+                            let src: Vec<u8> = (0..n as u8).map(|b| i ^ b).collect();
+                            grant.put_slice(&src);
+                            model.extend(&src);
+                        }
+                    }
+                    2 => {
+                        let n = next(8).min(model.len());
+                        buf.advance(n);
+                        let read = model.drain(..n);
+                        if let Some((_, consumed)) = checkpoint.as_mut() {
+                            Vec::extend(consumed, read);
+                        }
+                    }
+                    3 => {
+                        buf.make_contiguous();
+                    }
+                    4 => {
+                        let _ = buf.try_reserve(next(8));
+                    }
+                    5 => {
+                        checkpoint = Some((buf.checkpoint(), Vec::new()));
+                    }
+                    _ => {
+                        if let Some((token, consumed)) = checkpoint.take() {
+                            let result = buf.rollback(token);
+                            assert!(result.is_ok() || !consumed.is_empty(), "seed {}", seed);
+                            if result.is_ok() {
+                                for &b in consumed.iter().rev() {
+                                    model.push_front(b);
+                                }
+                            }
+                        }
+                    }
+                }
+                let (first, second) = buf.as_slices();
+                let bytes: Vec<u8> = first.iter().chain(second).copied().collect();
+                assert_eq!(bytes, Vec::from(model.clone()), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn ringbuffer_rollback_write_model() {
+        for seed in 1..1000u64 {
+            let mut rng = seed;
+            let mut next = |n: u64| {
+                // xorshift64
+                rng ^= rng << 13;
+                rng ^= rng >> 7;
+                rng ^= rng << 17;
+                (rng % n) as usize
+            };
+            let mut buf = if seed % 2 == 0 {
+                RingBuffer::new(16)
+            } else {
+                RingBuffer::growable(8)
+            };
+            let mut model = VecDeque::<u8>::new();
+            let mut pushed = 0;
+            let mut checkpoint = None;
+            for i in 0..50u8 {
+                match next(6) {
+                    0 => {
+                        let n = next(8);
+                        let src: Vec<u8> = (0..n as u8).map(|b| i ^ b).collect();
+                        if buf.try_put_slice(&src).is_ok() {
+                            model.extend(&src);
+                            pushed += n;
+                        }
+                    }
+                    1 => {
+                        let n = next(8);
+                        if let Ok(mut grant) = buf.grant_exact(n) {
+                            let src: Vec<u8> = (0..n as u8).map(|b| i ^ b).collect();
+                            grant.put_slice(&src);
+                            model.extend(&src);
+                            pushed += n;
+                        }
+                    }
+                    2 => {
+                        let n = next(8).min(model.len());
+                        buf.advance(n);
+                        model.drain(..n);
+                    }
+                    3 => {
+                        buf.make_contiguous();
+                    }
+                    4 => {
+                        checkpoint = Some((buf.write_checkpoint(), pushed));
+                    }
+                    _ => {
+                        if let Some((token, at)) = checkpoint.take() {
+                            let cnt = pushed - at;
+                            let result = buf.rollback_write(token);
+                            assert_eq!(result.is_ok(), cnt <= model.len(), "seed {}", seed);
+                            if result.is_ok() {
+                                model.truncate(model.len() - cnt);
+                                pushed = at;
+                            }
+                        }
+                    }
+                }
+                let (first, second) = buf.as_slices();
+                let bytes: Vec<u8> = first.iter().chain(second).copied().collect();
+                assert_eq!(bytes, Vec::from(model.clone()), "seed {}", seed);
+            }
+        }
+    }
+}
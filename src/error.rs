@@ -0,0 +1,42 @@
+//! Errors of the fallible API
+use core::fmt;
+
+/// Error returned if a ringbuffer does not have enough space or readable bytes
+/// for an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    requested: usize,
+    available: usize,
+}
+
+impl CapacityError {
+    pub(crate) fn new(requested: usize, available: usize) -> Self {
+        Self {
+            requested,
+            available,
+        }
+    }
+
+    /// Number of bytes the operation needed
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// Number of bytes that were available
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "requested {} bytes but only {} are available",
+            self.requested, self.available
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
@@ -0,0 +1,221 @@
+//! Operations that return an error instead of panicking
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+#[cfg(feature = "no-panic")]
+use no_panic::no_panic;
+
+use super::{CapacityError, Mode, RingBuffer, Storage};
+
+#[cfg(feature = "alloc")]
+impl RingBuffer {
+    /// Create a ringbuffer with the given capacity
+    ///
+    /// Returns an error instead of aborting if the memory cannot be allocated.
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub fn try_new(capacity: usize) -> Result<Self, CapacityError> {
+        let mut buffer = Vec::new();
+        if buffer.try_reserve_exact(capacity).is_err() {
+            return Err(CapacityError::new(capacity, 0));
+        }
+        // Safe because `MaybeUninit` does not need to be initialized.
+        unsafe { buffer.set_len(capacity) };
+        Ok(Self::from_storage(buffer))
+    }
+}
+
+impl<S: Storage> RingBuffer<S> {
+    /// Consume `cnt` readable bytes
+    ///
+    /// Returns an error if fewer bytes are readable.
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub fn try_advance(&mut self, cnt: usize) -> Result<(), CapacityError> {
+        if cnt > self.len {
+            return Err(CapacityError::new(cnt, self.len));
+        }
+        self.consume(cnt);
+        Ok(())
+    }
+
+    /// Reserve space for at least `additional` more bytes to be written
+    ///
+    /// Returns an error if the free space is too small and the storage cannot
+    /// grow. See [reserve](#method.reserve).
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), CapacityError> {
+        self.unpad();
+        let free = self.capacity() - self.len;
+        if additional <= free {
+            return Ok(());
+        }
+        self.try_grow(additional, free)
+    }
+
+    /// Write all of `src` to the buffer
+    ///
+    /// Returns an error without writing anything if `src` does not fit. A
+    /// growable buffer only fails if its storage cannot grow and a buffer in
+    /// overwrite mode never fails.
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub fn try_put_slice(&mut self, src: &[u8]) -> Result<(), CapacityError> {
+        let mut src = src;
+        if self.mode == Mode::Overwrite {
+            self.unpad();
+        }
+        let free = self.capacity() - self.len - self.padding;
+        if src.len() > free {
+            match self.mode {
+                Mode::Fixed => return Err(CapacityError::new(src.len(), free)),
+                Mode::Growable => self.try_reserve(src.len())?,
+                Mode::Overwrite => {
+                    if src.len() >= self.capacity() {
+                        // Only the end of `src` survives
+                        let skip = src.len() - self.capacity();
+                        self.dropped += (self.len + skip) as u64;
+                        self.begin = 0;
+                        self.len = 0;
+                        src = src.get(skip..).unwrap_or_default();
+                    }
+                    let evict = src.len().saturating_sub(self.capacity() - self.len);
+                    self.begin = self.wrap(self.begin + evict);
+                    self.len -= evict;
+                    self.dropped += evict as u64;
+                }
+            }
+        }
+
+        let (first, second) = self.writable_ranges();
+        let buffer = self.buffer.as_mut_slice();
+        let mut cnt = 0;
+        for range in [first, second].iter() {
+            if let Some(dst) = buffer.get_mut(range.clone()) {
+                cnt += copy_to_uninit(dst, src.get(cnt..).unwrap_or_default());
+            }
+        }
+        self.len += cnt;
+        self.track_written();
+        Ok(())
+    }
+
+    /// Grow the storage so that `additional` more bytes fit and move the
+    /// wrapped bytes behind the others
+    ///
+    /// Kept out of line so that only buffers which can actually grow pay for it.
+    #[inline(never)]
+    fn try_grow(&mut self, additional: usize, free: usize) -> Result<(), CapacityError> {
+        let old_capacity = self.capacity();
+        let capacity = match self.len.checked_add(additional) {
+            Some(capacity) => capacity.max(old_capacity.saturating_mul(2)),
+            None => return Err(CapacityError::new(additional, free)),
+        };
+        // The wrapped bytes are always at the start of the storage
+        let wrapped = self.readable_ranges().1.len();
+        if !self.buffer.grow(capacity) {
+            return Err(CapacityError::new(additional, free));
+        }
+        self.rearranged += 1;
+        // Move the wrapped bytes behind the ones at the end of the old storage
+        let buffer = self.buffer.as_mut_slice();
+        if old_capacity <= buffer.len() {
+            let (head, tail) = buffer.split_at_mut(old_capacity);
+            if let (Some(src), Some(dst)) = (head.get(..wrapped), tail.get_mut(..wrapped)) {
+                dst.copy_from_slice(src);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy as many bytes from `src` to `dst` as fit with a single `memcpy`
+#[inline]
+fn copy_to_uninit(dst: &mut [MaybeUninit<u8>], src: &[u8]) -> usize {
+    let cnt = dst.len().min(src.len());
+    // Safe because both slices are valid for `cnt` bytes and cannot overlap as
+    // one of them is borrowed mutably.
+    unsafe { ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr() as *mut u8, cnt) };
+    cnt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayRingBuffer;
+    #[cfg(feature = "alloc")]
+    use crate::Buf;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_try_new() {
+        let buf = RingBuffer::try_new(16).unwrap();
+        assert_eq!(buf.capacity(), 16);
+        assert!(RingBuffer::try_new(usize::MAX).is_err());
+
+        // A zero-capacity buffer is useless but must not panic
+        let mut buf = RingBuffer::try_new(0).unwrap();
+        assert!(buf.try_put_slice(&[]).is_ok());
+        assert!(buf.try_advance(0).is_ok());
+        assert_eq!(buf.try_put_slice(&[0]), Err(CapacityError::new(1, 0)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_try_put_advance() {
+        let mut buf = RingBuffer::new(4);
+        buf.try_put_slice(&[0, 1, 2]).unwrap();
+        let err = buf.try_put_slice(&[3, 4]).unwrap_err();
+        assert_eq!(err.requested(), 2);
+        assert_eq!(err.available(), 1);
+        assert_eq!(buf.remaining(), 3);
+
+        buf.try_advance(2).unwrap();
+        assert_eq!(buf.try_advance(2), Err(CapacityError::new(2, 1)));
+        buf.try_put_slice(&[3, 4, 5]).unwrap();
+        assert_eq!(buf.as_slices(), (&[2, 3][..], &[4, 5][..]));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_try_put_overwrite() {
+        let mut buf = RingBuffer::with_overwrite(4);
+        buf.try_put_slice(&[0, 1, 2]).unwrap();
+        buf.try_put_slice(&[3, 4]).unwrap();
+        assert_eq!(buf.dropped(), 1);
+        buf.try_put_slice(&[5, 6, 7, 8, 9, 10]).unwrap();
+        assert_eq!(buf.dropped(), 7);
+        assert_eq!(buf.get_u32(), 0x0708_090a);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_try_reserve() {
+        let mut buf = RingBuffer::growable(4);
+        buf.try_put_slice(&[0, 1, 2]).unwrap();
+        buf.try_advance(2).unwrap();
+        buf.try_put_slice(&[3, 4, 5, 6, 7]).unwrap();
+        assert_eq!(buf.bytes(), &[2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn ringbuffer_try_put_array() {
+        let mut buf = ArrayRingBuffer::<4>::default();
+        buf.try_put_slice(&[0, 1, 2]).unwrap();
+        buf.try_advance(2).unwrap();
+        buf.try_put_slice(&[3, 4, 5]).unwrap();
+        assert_eq!(buf.try_put_slice(&[6]), Err(CapacityError::new(1, 0)));
+        assert_eq!(buf.as_slices(), (&[2, 3][..], &[4, 5][..]));
+
+        let mut buf = RingBuffer::from_storage_with_overwrite([MaybeUninit::uninit(); 4]);
+        buf.try_put_slice(&[0, 1, 2]).unwrap();
+        buf.try_put_slice(&[3, 4, 5, 6, 7]).unwrap();
+        assert_eq!(buf.as_slices(), (&[4, 5, 6, 7][..], &[][..]));
+    }
+
+    #[test]
+    fn ringbuffer_try_reserve_array() {
+        let mut buf = ArrayRingBuffer::<4>::default();
+        buf.try_reserve(4).unwrap();
+        assert_eq!(buf.try_reserve(5), Err(CapacityError::new(5, 4)));
+    }
+}
@@ -0,0 +1,392 @@
+//! Length-delimited frames on top of a [RingBuffer](../struct.RingBuffer.html)
+//!
+//! Every frame is preceded by its length encoded as a [Prefix](enum.Prefix.html).
+//!
+//! ```
+//! # extern crate bytes_ringbuffer;
+//! # use bytes_ringbuffer::*;
+//! # use bytes_ringbuffer::frame::*;
+//! # #[cfg(not(feature = "alloc"))]
+//! # fn main() {}
+//! # #[cfg(feature = "alloc")]
+//! # fn main() {
+//! let codec = FrameCodec::new(Prefix::U16, 1024);
+//! let mut buf = RingBuffer::new(64);
+//! codec.push_frame(&mut buf, b"hello").unwrap();
+//! match codec.pop_frame(&mut buf).unwrap() {
+//!     Decoded::Frame(frame) => assert_eq!(&frame[..], b"hello"),
+//!     Decoded::NeedMore(_) => unreachable!(),
+//! }
+//! # }
+//! ```
+use core::fmt;
+
+use bytes::{Buf, BufMut, Bytes};
+
+use super::{CapacityError, RingBuffer, Storage};
+
+/// Encoding of the length that precedes a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prefix {
+    /// Single byte
+    U8,
+    /// Big-endian `u16`
+    U16,
+    /// Little-endian `u16`
+    U16Le,
+    /// Big-endian `u32`
+    U32,
+    /// Little-endian `u32`
+    U32Le,
+    /// Unsigned LEB128 of at most 10 bytes
+    Varint,
+}
+
+/// Maximum length of an encoded varint prefix
+const VARINT_MAX_LEN: usize = 10;
+
+impl Prefix {
+    /// Largest frame length that can be encoded
+    pub fn max_frame_len(self) -> usize {
+        match self {
+            Prefix::U8 => u8::MAX as usize,
+            Prefix::U16 | Prefix::U16Le => u16::MAX as usize,
+            Prefix::U32 | Prefix::U32Le => (u32::MAX as u64).min(usize::MAX as u64) as usize,
+            Prefix::Varint => usize::MAX,
+        }
+    }
+
+    /// Encode `len` into `dst` and return the number of bytes used
+    fn encode(self, len: usize, dst: &mut [u8; VARINT_MAX_LEN]) -> usize {
+        match self {
+            Prefix::U8 => {
+                dst[0] = len as u8;
+                1
+            }
+            Prefix::U16 => {
+                dst[..2].copy_from_slice(&(len as u16).to_be_bytes());
+                2
+            }
+            Prefix::U16Le => {
+                dst[..2].copy_from_slice(&(len as u16).to_le_bytes());
+                2
+            }
+            Prefix::U32 => {
+                dst[..4].copy_from_slice(&(len as u32).to_be_bytes());
+                4
+            }
+            Prefix::U32Le => {
+                dst[..4].copy_from_slice(&(len as u32).to_le_bytes());
+                4
+            }
+            Prefix::Varint => {
+                let mut len = len as u64;
+                let mut cnt = 0;
+                while len >= 0x80 {
+                    dst[cnt] = len as u8 | 0x80;
+                    len >>= 7;
+                    cnt += 1;
+                }
+                dst[cnt] = len as u8;
+                cnt + 1
+            }
+        }
+    }
+
+    /// Decode the prefix at the start of the readable bytes of `buf`
+    ///
+    /// Returns the length of the prefix and the decoded frame length, or the
+    /// number of missing bytes if the prefix is incomplete.
+    fn decode<S: Storage>(
+        self,
+        buf: &RingBuffer<S>,
+    ) -> Result<Result<(usize, usize), usize>, FrameError> {
+        let fixed = |len: usize, value: Option<u64>| match value {
+            Some(value) => Ok(Ok((len, value as usize))),
+            None => Ok(Err(len - buf.remaining())),
+        };
+        match self {
+            Prefix::U8 => fixed(1, buf.get(0).map(u64::from)),
+            Prefix::U16 => fixed(2, buf.peek_u16(0).map(u64::from)),
+            Prefix::U16Le => fixed(2, buf.peek_u16_le(0).map(u64::from)),
+            Prefix::U32 => fixed(4, buf.peek_u32(0).map(u64::from)),
+            Prefix::U32Le => fixed(4, buf.peek_u32_le(0).map(u64::from)),
+            Prefix::Varint => {
+                let mut len = 0u64;
+                for i in 0..VARINT_MAX_LEN {
+                    let byte = match buf.get(i) {
+                        Some(byte) => byte,
+                        None => return Ok(Err(1)),
+                    };
+                    len |= u64::from(byte & 0x7f) << (7 * i);
+                    if byte & 0x80 == 0 {
+                        if len > usize::MAX as u64 || (i == VARINT_MAX_LEN - 1 && byte > 1) {
+                            return Err(FrameError::InvalidPrefix);
+                        }
+                        return Ok(Ok((i + 1, len as usize)));
+                    }
+                }
+                Err(FrameError::InvalidPrefix)
+            }
+        }
+    }
+}
+
+/// Result of popping a frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decoded {
+    /// A complete frame without its prefix
+    Frame(Bytes),
+    /// The buffer needs at least this many more bytes before the next frame
+    /// can be popped
+    NeedMore(usize),
+}
+
+/// Error returned when pushing or popping a frame fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The frame is longer than the maximum frame length
+    TooLong {
+        /// Length of the frame
+        len: usize,
+        /// Maximum frame length
+        max: usize,
+    },
+    /// The frame and its prefix do not fit into the buffer
+    Capacity(CapacityError),
+    /// The varint prefix is malformed or does not fit into a `usize`
+    InvalidPrefix,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::TooLong { len, max } => {
+                write!(f, "frame of {} bytes exceeds the maximum of {}", len, max)
+            }
+            FrameError::Capacity(err) => write!(f, "frame does not fit: {}", err),
+            FrameError::InvalidPrefix => write!(f, "invalid length prefix"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FrameError {}
+
+impl From<CapacityError> for FrameError {
+    fn from(err: CapacityError) -> Self {
+        FrameError::Capacity(err)
+    }
+}
+
+/// Pushes and pops length-delimited frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameCodec {
+    prefix: Prefix,
+    max_frame_len: usize,
+}
+
+impl FrameCodec {
+    /// Create a codec for frames of at most `max_frame_len` bytes
+    ///
+    /// `max_frame_len` is limited to what `prefix` can encode.
+    pub fn new(prefix: Prefix, max_frame_len: usize) -> Self {
+        Self {
+            prefix,
+            max_frame_len: max_frame_len.min(prefix.max_frame_len()),
+        }
+    }
+
+    /// Encoding of the length prefix
+    pub fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    /// Maximum length of a frame without its prefix
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Write `frame` preceded by its length to `buf`
+    ///
+    /// Nothing is written if the frame is too long or does not fit. In
+    /// overwrite mode pushing a frame can evict parts of older frames.
+    pub fn push_frame<S: Storage>(
+        &self,
+        buf: &mut RingBuffer<S>,
+        frame: &[u8],
+    ) -> Result<(), FrameError> {
+        if frame.len() > self.max_frame_len {
+            return Err(FrameError::TooLong {
+                len: frame.len(),
+                max: self.max_frame_len,
+            });
+        }
+        let mut header = [0; VARINT_MAX_LEN];
+        let header_len = self.prefix.encode(frame.len(), &mut header);
+        let len = header_len + frame.len();
+        // Overwrite mode would evict the start of the frame itself
+        if !buf.is_growable() && len > buf.capacity() {
+            return Err(CapacityError::new(len, buf.capacity()).into());
+        }
+        if len > buf.remaining_mut() {
+            return Err(CapacityError::new(len, buf.remaining_mut()).into());
+        }
+        buf.put_slice(&header[..header_len]);
+        buf.put_slice(frame);
+        Ok(())
+    }
+
+    /// Remove the next frame from `buf`
+    ///
+    /// The prefix is peeked first and nothing is consumed unless the whole
+    /// frame is readable. A frame that is too long is left in the buffer.
+    pub fn pop_frame<S: Storage>(&self, buf: &mut RingBuffer<S>) -> Result<Decoded, FrameError> {
+        let (header_len, len) = match self.prefix.decode(buf)? {
+            Ok(header) => header,
+            Err(missing) => return Ok(Decoded::NeedMore(missing)),
+        };
+        if len > self.max_frame_len {
+            return Err(FrameError::TooLong {
+                len,
+                max: self.max_frame_len,
+            });
+        }
+        let total = match header_len.checked_add(len) {
+            Some(total) => total,
+            None => {
+                return Err(FrameError::TooLong {
+                    len,
+                    max: usize::MAX - header_len,
+                })
+            }
+        };
+        if total > buf.remaining() {
+            return Ok(Decoded::NeedMore(total - buf.remaining()));
+        }
+        buf.advance(header_len);
+        Ok(Decoded::Frame(buf.split_to(len)))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::wrapped;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn pop(codec: &FrameCodec, buf: &mut RingBuffer) -> Vec<u8> {
+        match codec.pop_frame(buf).unwrap() {
+            Decoded::Frame(frame) => frame.to_vec(),
+            Decoded::NeedMore(cnt) => panic!("need {} more bytes", cnt),
+        }
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let prefixes = [
+            Prefix::U8,
+            Prefix::U16,
+            Prefix::U16Le,
+            Prefix::U32,
+            Prefix::U32Le,
+            Prefix::Varint,
+        ];
+        for prefix in prefixes.iter() {
+            let codec = FrameCodec::new(*prefix, 300);
+            // Make the frames wrap around the end of the storage
+            let mut buf = wrapped(RingBuffer::new(512), 12, &[]);
+
+            let long = vec![7; codec.max_frame_len()];
+            codec.push_frame(&mut buf, b"hello").unwrap();
+            codec.push_frame(&mut buf, b"").unwrap();
+            codec.push_frame(&mut buf, &long).unwrap();
+            assert_eq!(pop(&codec, &mut buf), b"hello");
+            assert_eq!(pop(&codec, &mut buf), b"");
+            assert_eq!(pop(&codec, &mut buf), long);
+            assert_eq!(buf.remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn frame_need_more() {
+        let codec = FrameCodec::new(Prefix::U32, 1024);
+        let mut buf = RingBuffer::new(16);
+        buf.put_u16(0);
+        assert_eq!(codec.pop_frame(&mut buf), Ok(Decoded::NeedMore(2)));
+        buf.put_u16(5);
+        assert_eq!(codec.pop_frame(&mut buf), Ok(Decoded::NeedMore(5)));
+        buf.put_slice(b"abc");
+        assert_eq!(codec.pop_frame(&mut buf), Ok(Decoded::NeedMore(2)));
+        assert_eq!(buf.remaining(), 7);
+        buf.put_slice(b"de");
+        assert_eq!(pop(&codec, &mut buf), b"abcde");
+    }
+
+    #[test]
+    fn frame_varint() {
+        let codec = FrameCodec::new(Prefix::Varint, 1 << 20);
+        let mut buf = RingBuffer::growable(4);
+        let frame = vec![1; 300];
+        codec.push_frame(&mut buf, &frame).unwrap();
+        assert_eq!(buf.peek_u16(0), Some(0xac02));
+        assert_eq!(pop(&codec, &mut buf), frame);
+
+        buf.put_slice(&[0xff; 11]);
+        assert_eq!(codec.pop_frame(&mut buf), Err(FrameError::InvalidPrefix));
+    }
+
+    #[test]
+    fn frame_errors() {
+        let codec = FrameCodec::new(Prefix::U8, 4);
+        let mut buf = RingBuffer::new(8);
+        assert_eq!(
+            codec.push_frame(&mut buf, b"hello"),
+            Err(FrameError::TooLong { len: 5, max: 4 })
+        );
+        codec.push_frame(&mut buf, b"abcd").unwrap();
+        assert_eq!(
+            codec.push_frame(&mut buf, b"efgh"),
+            Err(FrameError::Capacity(CapacityError::new(5, 3)))
+        );
+        assert_eq!(pop(&codec, &mut buf), b"abcd");
+
+        buf.put_slice(&[5, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            codec.pop_frame(&mut buf),
+            Err(FrameError::TooLong { len: 5, max: 4 })
+        );
+        assert_eq!(buf.remaining(), 6);
+    }
+
+    #[test]
+    fn frame_overwrite() {
+        let codec = FrameCodec::new(Prefix::U8, 255);
+        let mut buf = RingBuffer::with_overwrite(4);
+        assert_eq!(
+            codec.push_frame(&mut buf, b"abcdef"),
+            Err(FrameError::Capacity(CapacityError::new(7, 4)))
+        );
+        assert_eq!(buf.remaining(), 0);
+        codec.push_frame(&mut buf, b"abc").unwrap();
+        assert_eq!(pop(&codec, &mut buf), b"abc");
+    }
+
+    #[test]
+    fn frame_varint_overflow() {
+        let codec = FrameCodec::new(Prefix::Varint, usize::MAX);
+        let mut buf = RingBuffer::new(16);
+        let mut header = [0; VARINT_MAX_LEN];
+        let header_len = Prefix::Varint.encode(usize::MAX, &mut header);
+        buf.put_slice(&header[..header_len]);
+        assert_eq!(
+            codec.pop_frame(&mut buf),
+            Err(FrameError::TooLong {
+                len: usize::MAX,
+                max: usize::MAX - header_len,
+            })
+        );
+        assert_eq!(buf.remaining(), header_len);
+    }
+}
@@ -0,0 +1,382 @@
+//! Write and read grants in the style of bbqueue
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+use core::slice;
+
+use bytes::{Buf, BufMut};
+
+use super::{CapacityError, RingBuffer, Storage};
+
+/// Contiguous free space of a ringbuffer returned by
+/// [grant_exact](struct.RingBuffer.html#method.grant_exact) and
+/// [grant_max](struct.RingBuffer.html#method.grant_max)
+///
+/// The grant is filled through its `BufMut` implementation. The filled bytes
+/// become readable when the grant is committed or dropped.
+#[derive(Debug)]
+pub struct WriteGrant<'a, S: Storage> {
+    buf: &'a mut RingBuffer<S>,
+    start: usize,
+    len: usize,
+    /// Bytes at the end of the storage that are skipped if anything is committed
+    skip: usize,
+    filled: usize,
+}
+
+/// Contiguous readable bytes of a ringbuffer returned by
+/// [grant_read](struct.RingBuffer.html#method.grant_read)
+///
+/// Only the bytes passed to [release](#method.release) or
+/// [to_release](#method.to_release) are consumed when the grant is dropped.
+#[derive(Debug)]
+pub struct ReadGrant<'a, S: Storage> {
+    buf: &'a mut RingBuffer<S>,
+    release: usize,
+}
+
+/// Both segments of the readable bytes of a ringbuffer returned by
+/// [grant_split_read](struct.RingBuffer.html#method.grant_split_read)
+///
+/// Works like a [ReadGrant](struct.ReadGrant.html), but releasing can cross
+/// from the first segment into the second.
+#[derive(Debug)]
+pub struct SplitReadGrant<'a, S: Storage> {
+    buf: &'a mut RingBuffer<S>,
+    release: usize,
+}
+
+impl<S: Storage> RingBuffer<S> {
+    /// Grant exactly `n` contiguous bytes of free space
+    ///
+    /// If the free space at the end of the storage is too small but the free
+    /// space at its start is large enough, the end is skipped once something is
+    /// committed and counts against the capacity until it is read past. Returns
+    /// an error if neither is large enough. Grants never grow the storage or
+    /// evict bytes.
+    pub fn grant_exact(&mut self, n: usize) -> Result<WriteGrant<'_, S>, CapacityError> {
+        let (tail, head) = self.grantable();
+        if n <= tail.1 {
+            Ok(self.grant(tail.0, n, 0))
+        } else if n <= head {
+            Ok(self.grant(0, n, tail.1))
+        } else {
+            Err(CapacityError::new(n, tail.1.max(head)))
+        }
+    }
+
+    /// Grant up to `n` contiguous bytes of free space
+    ///
+    /// The free space at the end of the storage is granted if there is any, so
+    /// nothing is skipped. Returns an error if there is no free space and `n` is
+    /// not zero.
+    pub fn grant_max(&mut self, n: usize) -> Result<WriteGrant<'_, S>, CapacityError> {
+        let (tail, head) = self.grantable();
+        if tail.1 > 0 || n == 0 {
+            Ok(self.grant(tail.0, n.min(tail.1), 0))
+        } else if head > 0 {
+            Ok(self.grant(0, n.min(head), tail.1))
+        } else {
+            Err(CapacityError::new(n, 0))
+        }
+    }
+
+    /// Start and length of the free space at the write position and the length
+    /// of the contiguous space at the start of the storage if that is skipped
+    fn grantable(&self) -> ((usize, usize), usize) {
+        let (first, second) = self.free_ranges();
+        // Once an empty buffer skips the end it starts over at the beginning
+        let head = if self.len == 0 && self.padding == 0 {
+            self.capacity()
+        } else {
+            second.len()
+        };
+        ((first.start, first.len()), head)
+    }
+
+    /// Borrow the readable bytes up to the end of the storage
+    ///
+    /// This is bbqueue's `read`, which would shadow `std::io::Read::read` here.
+    pub fn grant_read(&mut self) -> ReadGrant<'_, S> {
+        ReadGrant {
+            buf: self,
+            release: 0,
+        }
+    }
+
+    /// Borrow both segments of the readable bytes
+    pub fn grant_split_read(&mut self) -> SplitReadGrant<'_, S> {
+        SplitReadGrant {
+            buf: self,
+            release: 0,
+        }
+    }
+
+    fn grant(&mut self, start: usize, len: usize, skip: usize) -> WriteGrant<'_, S> {
+        WriteGrant {
+            buf: self,
+            start,
+            len,
+            skip,
+            filled: 0,
+        }
+    }
+}
+
+impl<'a, S: Storage> WriteGrant<'a, S> {
+    /// Number of granted bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing was granted
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bytes filled so far, e.g. to patch a length prefix
+    pub fn filled_mut(&mut self) -> &mut [u8] {
+        let start = self.start;
+        let filled = &mut self.buf.buffer.as_mut_slice()[start..start + self.filled];
+        // Safe because the filled bytes have been initialized by `advance_mut`.
+        unsafe { slice::from_raw_parts_mut(filled.as_mut_ptr() as *mut u8, filled.len()) }
+    }
+
+    /// Make the first `cnt` filled bytes readable and discard the rest
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `cnt` bytes have been filled.
+    pub fn commit(mut self, cnt: usize) {
+        assert!(cnt <= self.filled);
+        self.filled = cnt;
+    }
+}
+
+impl<'a, S: Storage> BufMut for WriteGrant<'a, S> {
+    fn remaining_mut(&self) -> usize {
+        self.len - self.filled
+    }
+
+    fn bytes_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        let start = self.start;
+        &mut self.buf.buffer.as_mut_slice()[start + self.filled..start + self.len]
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(cnt <= self.len - self.filled);
+        self.filled += cnt;
+    }
+}
+
+impl<'a, S: Storage> Drop for WriteGrant<'a, S> {
+    fn drop(&mut self) {
+        if self.filled == 0 {
+            return;
+        }
+        let buf = &mut *self.buf;
+        if self.skip > 0 {
+            buf.skipped = buf.skipped.wrapping_add(self.skip as u64);
+            if buf.len == 0 {
+                // Nothing is readable, so the skipped end counts as read
+                buf.begin = 0;
+                buf.read = buf.read.wrapping_add(self.skip as u64);
+                buf.last_padding = self.skip;
+            } else {
+                buf.padding = self.skip;
+            }
+        }
+        buf.len += self.filled;
+        buf.track_written();
+    }
+}
+
+impl<'a, S: Storage> ReadGrant<'a, S> {
+    /// Consume the first `cnt` bytes and drop the grant
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grant is shorter than `cnt` bytes.
+    pub fn release(mut self, cnt: usize) {
+        self.to_release(cnt);
+    }
+
+    /// Consume the first `cnt` bytes when the grant is dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grant is shorter than `cnt` bytes.
+    pub fn to_release(&mut self, cnt: usize) {
+        assert!(cnt <= self.len());
+        self.release = cnt;
+    }
+}
+
+impl<'a, S: Storage> Deref for ReadGrant<'a, S> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.buf.as_slices().0
+    }
+}
+
+impl<'a, S: Storage> Drop for ReadGrant<'a, S> {
+    fn drop(&mut self) {
+        self.buf.advance(self.release);
+    }
+}
+
+impl<'a, S: Storage> SplitReadGrant<'a, S> {
+    /// Both segments, in order
+    pub fn bufs(&self) -> (&[u8], &[u8]) {
+        self.buf.as_slices()
+    }
+
+    /// Total length of both segments
+    pub fn combined_len(&self) -> usize {
+        self.buf.remaining()
+    }
+
+    /// Consume the first `cnt` bytes and drop the grant
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grant is shorter than `cnt` bytes.
+    pub fn release(mut self, cnt: usize) {
+        self.to_release(cnt);
+    }
+
+    /// Consume the first `cnt` bytes when the grant is dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grant is shorter than `cnt` bytes.
+    pub fn to_release(&mut self, cnt: usize) {
+        assert!(cnt <= self.combined_len());
+        self.release = cnt;
+    }
+}
+
+impl<'a, S: Storage> Drop for SplitReadGrant<'a, S> {
+    fn drop(&mut self) {
+        self.buf.advance(self.release);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::wrapped;
+
+    #[test]
+    fn ringbuffer_grant_exact() {
+        let mut buf = RingBuffer::new(8);
+        buf.put_slice(&[0, 1, 2, 3, 4, 5]);
+        buf.advance(4);
+
+        // Skip the 2 bytes at the end
+        let mut grant = buf.grant_exact(3).unwrap();
+        grant.put_u16(0x0607);
+        grant.put_u8(8);
+        assert_eq!(grant.remaining_mut(), 0);
+        drop(grant);
+        assert_eq!(buf.as_slices(), (&[4, 5][..], &[6, 7, 8][..]));
+        assert_eq!(buf.remaining_mut(), 1);
+        assert_eq!(buf.grant_exact(2).unwrap_err(), CapacityError::new(2, 1));
+
+        // The padding is skipped on the way
+        assert_eq!(buf.get_u32(), 0x0405_0607);
+        assert_eq!(buf.remaining_mut(), 7);
+        buf.put_slice(&[9, 10, 11, 12, 13, 14]);
+        assert_eq!(buf.as_slices(), (&[8, 9, 10, 11, 12, 13][..], &[14][..]));
+
+        // An empty buffer starts over at the beginning
+        buf.advance(7);
+        let mut grant = buf.grant_exact(8).unwrap();
+        grant.put_slice(&[1; 8]);
+        grant.commit(6);
+        assert_eq!(buf.as_slices(), (&[1; 6][..], &[][..]));
+    }
+
+    #[test]
+    fn ringbuffer_grant_max() {
+        let mut buf = wrapped(RingBuffer::new(8), 4, &[0, 0]);
+
+        let mut grant = buf.grant_max(4).unwrap();
+        assert_eq!(grant.len(), 2);
+        grant.put_u8(1);
+        grant.filled_mut()[0] = 2;
+        drop(grant);
+        assert_eq!(buf.bytes(), &[0, 0, 2]);
+
+        // Dropping an unfilled grant skips nothing
+        let grant = buf.grant_max(8).unwrap();
+        assert_eq!(grant.len(), 1);
+        drop(grant);
+        buf.put_u8(3);
+        let grant = buf.grant_max(8).unwrap();
+        assert_eq!(grant.len(), 4);
+        drop(grant);
+        assert_eq!(buf.remaining_mut(), 4);
+    }
+
+    #[test]
+    fn ringbuffer_grant_rollback() {
+        let mut buf = wrapped(RingBuffer::new(8), 4, &[0, 0]);
+
+        let read = buf.checkpoint();
+        let write = buf.write_checkpoint();
+        let mut grant = buf.grant_exact(3).unwrap();
+        grant.put_slice(&[1, 2, 3]);
+        drop(grant);
+        buf.rollback_write(write).unwrap();
+        assert_eq!(buf.as_slices(), (&[0, 0][..], &[][..]));
+        assert_eq!(buf.remaining_mut(), 6);
+
+        let mut grant = buf.grant_exact(3).unwrap();
+        grant.put_slice(&[1, 2, 3]);
+        drop(grant);
+        buf.advance(3);
+        buf.rollback(read).unwrap();
+        assert_eq!(buf.as_slices(), (&[0, 0][..], &[1, 2, 3][..]));
+
+        // Growing moves the bytes in front of the padding
+        let mut buf = wrapped(RingBuffer::growable(8), 4, &[0, 0]);
+        buf.grant_exact(3).unwrap().put_slice(&[1, 2, 3]);
+        buf.put_slice(&[0; 4]);
+        assert_eq!(buf.remaining(), 9);
+        assert_eq!(buf.as_slices().0, &[0, 0, 1, 2, 3, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn ringbuffer_read_grant() {
+        let mut buf = wrapped(RingBuffer::new(8), 2, &[1, 2, 3, 4, 5]);
+
+        let grant = buf.grant_read();
+        assert_eq!(&grant[..], &[1, 2]);
+        let sum: u32 = grant.iter().map(|b| u32::from(*b)).sum();
+        assert_eq!(sum, 3);
+        grant.release(1);
+        assert_eq!(buf.remaining(), 4);
+
+        // Nothing is released unless asked for
+        drop(buf.grant_read());
+        assert_eq!(buf.remaining(), 4);
+
+        let mut grant = buf.grant_split_read();
+        assert_eq!(grant.bufs(), (&[2][..], &[3, 4, 5][..]));
+        assert_eq!(grant.combined_len(), 4);
+        grant.to_release(3);
+        drop(grant);
+        assert_eq!(buf.bytes(), &[5]);
+
+        // Reading over a padding only shows the bytes in front of it
+        buf.put_slice(&[6, 7, 8, 9]);
+        buf.advance(4);
+        buf.grant_exact(5).unwrap().put_slice(&[10, 11, 12, 13, 14]);
+        assert_eq!(&buf.grant_read()[..], &[9]);
+        let grant = buf.grant_split_read();
+        assert_eq!(grant.bufs(), (&[9][..], &[10, 11, 12, 13, 14][..]));
+        grant.release(2);
+        assert_eq!(&buf.grant_read()[..], &[11, 12, 13, 14]);
+    }
+}
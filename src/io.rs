@@ -0,0 +1,91 @@
+//! Implementations of the `std::io` traits for `RingBuffer`
+use std::io::{self, BufRead, Read, Write};
+
+use super::{Buf, BufMut, RingBuffer, Storage};
+
+impl<S: Storage> Read for RingBuffer<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let cnt = buf.len().min(self.remaining());
+        self.copy_to_slice(&mut buf[..cnt]);
+        Ok(cnt)
+    }
+}
+
+impl<S: Storage> BufRead for RingBuffer<S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(Buf::bytes(self))
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.advance(amt);
+    }
+}
+
+impl<S: Storage> Write for RingBuffer<S> {
+    /// Write as many bytes as fit into the buffer
+    ///
+    /// Returns `Ok(0)` instead of panicking if the buffer is full.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let cnt = buf.len().min(self.remaining_mut());
+        self.put_slice(&buf[..cnt]);
+        Ok(cnt)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ringbuffer_io_read_write() {
+        let mut buf = RingBuffer::new(8);
+        assert_eq!(buf.write(&[0, 1, 2, 3, 4, 5]).unwrap(), 6);
+        let mut dst = [0; 4];
+        assert_eq!(buf.read(&mut dst).unwrap(), 4);
+        assert_eq!(dst, [0, 1, 2, 3]);
+
+        // Write across the wrap until the buffer is full
+        assert_eq!(buf.write(&[6, 7, 8, 9, 10, 11, 12, 13]).unwrap(), 6);
+        assert_eq!(buf.write(&[14]).unwrap(), 0);
+        assert_eq!(
+            buf.write_all(&[14]).unwrap_err().kind(),
+            io::ErrorKind::WriteZero
+        );
+
+        let mut dst = Vec::new();
+        assert_eq!(buf.read_to_end(&mut dst).unwrap(), 8);
+        assert_eq!(dst, [4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(buf.read(&mut [0; 4]).unwrap(), 0);
+    }
+
+    #[test]
+    fn ringbuffer_io_copy() {
+        let mut buf = RingBuffer::new(16);
+        let mut src: &[u8] = b"hello world";
+        assert_eq!(io::copy(&mut src, &mut buf).unwrap(), 11);
+        let mut dst = Vec::new();
+        assert_eq!(io::copy(&mut buf, &mut dst).unwrap(), 11);
+        assert_eq!(dst, b"hello world");
+    }
+
+    #[test]
+    fn ringbuffer_io_read_line() {
+        let mut buf = RingBuffer::new(8);
+        buf.put_slice(b"xxxxx");
+        buf.advance(5);
+        // The first line wraps around the end of the storage
+        buf.put_slice(b"ab\ncd\n");
+
+        let mut line = String::new();
+        assert_eq!(buf.read_line(&mut line).unwrap(), 3);
+        assert_eq!(line, "ab\n");
+        line.clear();
+        assert_eq!(buf.read_line(&mut line).unwrap(), 3);
+        assert_eq!(line, "cd\n");
+        assert_eq!(buf.fill_buf().unwrap(), b"");
+    }
+}
@@ -4,6 +4,9 @@
 //! ```
 //! # extern crate bytes_ringbuffer;
 //! # use bytes_ringbuffer::*;
+//! # #[cfg(not(feature = "alloc"))]
+//! # fn main() {}
+//! # #[cfg(feature = "alloc")]
 //! # fn main() {
 //! let mut buf = RingBuffer::new(4);
 //! buf.put_u16(1234);
@@ -12,72 +15,665 @@
 //! assert_eq!(buf.get_u16(), 5671);
 //! # }
 //! ```
+//!
+//! The storage does not have to be a heap allocated `Vec`. Any [Storage](trait.Storage.html)
+//! can be used, for example an array or a borrowed slice:
+//!
+//! ```
+//! # extern crate bytes_ringbuffer;
+//! # use bytes_ringbuffer::*;
+//! # fn main() {
+//! let mut buf = ArrayRingBuffer::<4>::default();
+//! buf.put_u32(1234);
+//! assert_eq!(buf.get_u32(), 1234);
+//!
+//! let mut memory = [std::mem::MaybeUninit::uninit(); 4];
+//! let mut buf = RingBuffer::from_slice(&mut memory);
+//! buf.put_u32(5671);
+//! assert_eq!(buf.get_u32(), 5671);
+//! # }
+//! ```
+//!
+//! # Cargo features
+//!
+//! * `std` (default): Implementations of the `std::io` traits, vectored I/O, the
+//!   [BlockingRingBuffer](struct.BlockingRingBuffer.html) and the
+//!   [MirroredRingBuffer](struct.MirroredRingBuffer.html) on Linux. Without it
+//!   the crate is `no_std`.
+//! * `alloc` (enabled by `std`): Heap allocated storage, the
+//!   [split](struct.RingBuffer.html#method.split) into producer and consumer,
+//!   the [SharedRingBuffer](struct.SharedRingBuffer.html) and the
+//!   [BroadcastRingBuffer](struct.BroadcastRingBuffer.html). Without it the crate
+//!   does not allocate by itself, but note that the `bytes` crate still links
+//!   against `alloc`. The storage of a `RingBuffer` has to be named then, as
+//!   only a `Vec` is used by default.
+//! * `bytes1`: Implement the `Buf` and `BufMut` traits of bytes 1.x in
+//!   addition to those of bytes 0.5, which are re-exported by this crate.
+//! * `futures-io`: An in-memory async [pipe](pipe/index.html) implementing the
+//!   `AsyncRead` and `AsyncWrite` traits of the
+//!   [futures-io](https://docs.rs/futures-io/0.3) crate.
+//! * `no-panic`: Verify at link time that `try_new`, `try_advance`,
+//!   `try_reserve` and `try_put_slice` cannot panic. Only works in optimized
+//!   builds, and growing a `Vec` is only verified if the calls end up in the
+//!   same codegen unit as this crate's code.
+//! * `serde`: Serialize a `RingBuffer` as its capacity, its mode and its
+//!   readable bytes in order as a sequence of `u8`. Deserializing requires
+//!   `alloc` and also accepts a byte string.
+//! * `tokio`: Read from `AsyncRead` and write to `AsyncWrite` implementations of
+//!   the [tokio](https://docs.rs/tokio/0.2) crate.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 extern crate bytes;
 
-use std::mem::MaybeUninit;
+/// Declare a struct whose storage `S` defaults to a `Vec` if `alloc` is enabled
+///
+/// Without `alloc` there is no default, so that enabling the feature does not
+/// change what a bare type name means.
+macro_rules! default_storage {
+    ($(#[$attr:meta])* pub struct $name:ident<S> $fields:tt) => {
+        #[cfg(feature = "alloc")]
+        $(#[$attr])*
+        pub struct $name<S = DefaultStorage> $fields
+
+        #[cfg(not(feature = "alloc"))]
+        $(#[$attr])*
+        pub struct $name<S> $fields
+    };
+}
+
+#[cfg(feature = "std")]
+mod blocking;
+#[cfg(feature = "alloc")]
+mod broadcast;
+#[cfg(feature = "bytes1")]
+mod bytes1_buf;
+mod checkpoint;
+mod error;
+mod fallible;
+pub mod frame;
+mod grant;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(all(target_os = "linux", feature = "std"))]
+mod mirrored;
+mod peek;
+#[cfg(feature = "futures-io")]
+pub mod pipe;
+mod record;
+mod search;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "alloc")]
+mod shared;
+#[cfg(feature = "alloc")]
+mod spsc;
+mod storage;
+#[cfg(feature = "tokio")]
+mod tokio_io;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use core::iter;
+use core::mem::{self, MaybeUninit};
+use core::ops::Range;
+use core::ptr;
+#[cfg(feature = "std")]
+use std::io::IoSlice;
 
+#[cfg(feature = "std")]
+pub use blocking::{BlockingError, BlockingRingBuffer};
+#[cfg(feature = "alloc")]
+pub use broadcast::{BroadcastRingBuffer, LaggedError, ReaderId};
+#[cfg(feature = "std")]
+use bytes::buf::IoSliceMut;
 pub use bytes::{Buf, BufMut};
+use bytes::{Bytes, BytesMut};
+pub use checkpoint::{ReadCheckpoint, WriteCheckpoint};
+pub use error::CapacityError;
+pub use grant::{ReadGrant, SplitReadGrant, WriteGrant};
+#[cfg(all(target_os = "linux", feature = "std"))]
+pub use mirrored::MirroredRingBuffer;
+pub use record::{RecordRing, Records};
+#[cfg(feature = "alloc")]
+pub use shared::{SharedRingBuffer, View};
+#[cfg(feature = "alloc")]
+pub use spsc::{Consumer, Producer};
+pub use storage::Storage;
+
+#[cfg(feature = "alloc")]
+type DefaultStorage = Vec<MaybeUninit<u8>>;
 
-/// Fixed-capacity buffer
-#[derive(Debug, Clone)]
-pub struct RingBuffer {
-    buffer: Vec<MaybeUninit<u8>>,
-    begin: usize,
-    len: usize,
+default_storage! {
+    /// Ringbuffer of bytes
+    ///
+    /// The bytes are stored in `S`, which is a heap allocated `Vec` by default.
+    /// Without the `alloc` feature there is no default, so use
+    /// [ArrayRingBuffer](type.ArrayRingBuffer.html) or name the storage.
+    #[derive(Debug, Clone)]
+    pub struct RingBuffer<S> {
+        buffer: S,
+        begin: usize,
+        len: usize,
+        mode: Mode,
+        dropped: u64,
+        /// Number of bytes at the end of the storage skipped by a write grant
+        padding: usize,
+        /// Padding skipped the last time the readable bytes wrapped around
+        last_padding: usize,
+        /// Stream position of the first readable byte
+        read: u64,
+        /// Highest stream position that has ever been written
+        written: u64,
+        /// Total number of padding bytes that are part of the stream
+        skipped: u64,
+        /// Number of times the readable bytes have been moved within the storage
+        rearranged: u64,
+    }
 }
 
+/// Ringbuffer that stores its bytes in an array of length `N`
+pub type ArrayRingBuffer<const N: usize> = RingBuffer<[MaybeUninit<u8>; N]>;
+
+/// Behaviour of a ringbuffer when writing to it while it is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Mode {
+    /// Writing over the capacity panics
+    Fixed,
+    /// Writing over the capacity evicts the oldest unread bytes
+    Overwrite,
+    /// Writing over the capacity reallocates the storage
+    Growable,
+}
+
+#[cfg(feature = "alloc")]
 impl RingBuffer {
     /// Create a ringbuffer with the given capacity
     pub fn new(capacity: usize) -> Self {
+        Self::with_mode(capacity, Mode::Fixed)
+    }
+
+    /// Create a ringbuffer with the given capacity that overwrites the oldest
+    /// unread bytes instead of panicking when it is full
+    ///
+    /// In this mode `remaining_mut()` is unbounded. The number of evicted bytes
+    /// can be queried with [dropped](#method.dropped).
+    pub fn with_overwrite(capacity: usize) -> Self {
+        Self::with_mode(capacity, Mode::Overwrite)
+    }
+
+    /// Create a ringbuffer with the given initial capacity that grows instead of
+    /// panicking when it is full
+    ///
+    /// In this mode `remaining_mut()` is unbounded like it is for `BytesMut`.
+    pub fn growable(capacity: usize) -> Self {
+        Self::with_mode(capacity, Mode::Growable)
+    }
+
+    fn with_mode(capacity: usize, mode: Mode) -> Self {
         Self {
             buffer: vec![MaybeUninit::uninit(); capacity],
             begin: 0,
             len: 0,
+            mode,
+            dropped: 0,
+            padding: 0,
+            last_padding: 0,
+            read: 0,
+            written: 0,
+            skipped: 0,
+            rearranged: 0,
+        }
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<[MaybeUninit<u8>; N]> {
+    fn default() -> Self {
+        Self::from_storage([MaybeUninit::uninit(); N])
+    }
+}
+
+impl<'a> RingBuffer<&'a mut [MaybeUninit<u8>]> {
+    /// Create an empty ringbuffer that uses the borrowed `slice` as its memory
+    pub const fn from_slice(slice: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self::from_storage(slice)
+    }
+}
+
+impl<S: Storage> RingBuffer<S> {
+    /// Create an empty ringbuffer that uses `storage` as its memory
+    pub const fn from_storage(storage: S) -> Self {
+        Self {
+            buffer: storage,
+            begin: 0,
+            len: 0,
+            mode: Mode::Fixed,
+            dropped: 0,
+            padding: 0,
+            last_padding: 0,
+            read: 0,
+            written: 0,
+            skipped: 0,
+            rearranged: 0,
+        }
+    }
+
+    /// Create an empty ringbuffer that uses `storage` as its memory and
+    /// overwrites the oldest unread bytes instead of panicking when it is full
+    ///
+    /// See [with_overwrite](#method.with_overwrite).
+    pub const fn from_storage_with_overwrite(storage: S) -> Self {
+        Self {
+            buffer: storage,
+            begin: 0,
+            len: 0,
+            mode: Mode::Overwrite,
+            dropped: 0,
+            padding: 0,
+            last_padding: 0,
+            read: 0,
+            written: 0,
+            skipped: 0,
+            rearranged: 0,
         }
     }
 
+    /// Consume the ringbuffer and return its memory
+    ///
+    /// Bytes that have not been read are lost.
+    pub fn into_storage(self) -> S {
+        self.buffer
+    }
+
     /// Capacity of the ringbuffer
     pub fn capacity(&self) -> usize {
-        self.buffer.len()
+        self.buffer.as_slice().len()
+    }
+
+    /// Whether the oldest unread bytes are overwritten when the buffer is full
+    pub fn is_overwrite(&self) -> bool {
+        self.mode == Mode::Overwrite
+    }
+
+    /// Whether the storage is reallocated when the buffer is full
+    pub fn is_growable(&self) -> bool {
+        self.mode == Mode::Growable
+    }
+
+    /// Total number of unread bytes that have been overwritten
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Reserve space for at least `additional` more bytes to be written
+    ///
+    /// If the free space is too small the storage is grown and the readable
+    /// bytes are moved so that they are contiguous afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the free space is too small and the storage cannot grow. See
+    /// [try_reserve](#method.try_reserve) for a fallible version.
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve(additional) {
+            panic!("storage cannot grow: {}", err);
+        }
+    }
+
+    /// Both segments of the readable bytes, in order
+    ///
+    /// The second segment is non-empty only if the readable bytes wrap around the
+    /// end of the storage. Nothing is moved.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let (first, second) = self.readable_ranges();
+        let buffer = self.buffer.as_slice();
+        // Safe because the ranges only cover bytes that have been declared
+        // initialized by the unsafe `BufMut::advance_mut` function.
+        unsafe {
+            (
+                &*(&buffer[first] as *const [MaybeUninit<u8>] as *const [u8]),
+                &*(&buffer[second] as *const [MaybeUninit<u8>] as *const [u8]),
+            )
+        }
+    }
+
+    /// Remove the first `n` readable bytes and return them as `Bytes`
+    ///
+    /// The bytes are copied with at most two `memcpy` calls, one per segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `n` bytes are readable.
+    pub fn split_to(&mut self, n: usize) -> Bytes {
+        assert!(n <= self.len);
+        let (first, second) = self.as_slices();
+        let split = first.len().min(n);
+        let mut bytes = BytesMut::with_capacity(n);
+        bytes.extend_from_slice(&first[..split]);
+        bytes.extend_from_slice(&second[..n - split]);
+        self.advance(n);
+        bytes.freeze()
+    }
+
+    /// Rotate the storage in place so that all readable bytes are contiguous and
+    /// start at the beginning of the storage
+    ///
+    /// Returns the readable bytes as a single slice.
+    pub fn make_contiguous(&mut self) -> &mut [u8] {
+        self.unpad();
+        let buffer = self.buffer.as_mut_slice();
+        if self.begin != 0 {
+            buffer.rotate_left(self.begin);
+            self.begin = 0;
+            self.rearranged += 1;
+        }
+        let slice = &mut buffer[..self.len];
+        // Safe because `slice` contains exactly the readable bytes.
+        unsafe { &mut *(slice as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
+
+    /// Both segments of the space that is written to next, in order
+    ///
+    /// The second segment is non-empty only if the space wraps around the end of
+    /// the storage. Together they cover the same bytes as the slices returned by
+    /// `BufMut::bytes_vectored_mut`, so a single `readv` can fill them before
+    /// calling `BufMut::advance_mut` with the total.
+    pub fn free_slices_mut(&mut self) -> (&mut [MaybeUninit<u8>], &mut [MaybeUninit<u8>]) {
+        self.make_writable();
+        let (first, second) = self.writable_ranges();
+        // `second` always lies in front of `first`
+        let (head, tail) = self.buffer.as_mut_slice().split_at_mut(first.start);
+        (&mut tail[..first.len()], &mut head[second])
+    }
+
+    /// Make sure that a growable buffer has free space to write to and that
+    /// overwriting does not have to deal with padding
+    fn make_writable(&mut self) {
+        match self.mode {
+            Mode::Growable if self.len + self.padding == self.capacity() => self.reserve(64),
+            Mode::Overwrite => self.unpad(),
+            _ => {}
+        }
+    }
+
+    /// Move the readable bytes in front of the padding behind it so that the
+    /// padding disappears
+    fn unpad(&mut self) {
+        let padding = self.padding;
+        if padding == 0 {
+            return;
+        }
+        let limit = self.capacity() - padding;
+        let ptr = self.buffer.as_mut_slice().as_mut_ptr();
+        // Safe because `begin..limit` and `begin + padding..capacity` both lie
+        // within the storage, which is what `ptr::copy` expects.
+        unsafe {
+            ptr::copy(
+                ptr.add(self.begin),
+                ptr.add(self.begin + padding),
+                limit - self.begin,
+            )
+        };
+        // The stream position of the moved bytes does not change
+        self.begin += padding;
+        self.read = self.read.wrapping_add(padding as u64);
+        self.padding = 0;
+        self.rearranged += 1;
+    }
+
+    /// Consume `cnt <= len` readable bytes, skipping the padding
+    fn consume(&mut self, cnt: usize) {
+        let limit = self.capacity() - self.padding;
+        let mut begin = self.begin + cnt;
+        if begin >= limit {
+            begin -= limit;
+            self.read = self.read.wrapping_add(self.padding as u64);
+            self.last_padding = self.padding;
+            self.padding = 0;
+        }
+        self.begin = begin;
+        self.len -= cnt;
+        self.read = self.read.wrapping_add(cnt as u64);
+    }
+
+    /// Stream position behind the last written byte
+    ///
+    /// The padding is part of the stream, which keeps the storage position of a
+    /// byte equal to its stream position modulo the capacity.
+    fn end(&self) -> u64 {
+        self.read
+            .wrapping_add(self.dropped)
+            .wrapping_add(self.len as u64)
+            .wrapping_add(self.padding as u64)
+    }
+
+    /// Number of bytes that have ever been read, not counting padding
+    fn consumed(&self) -> u64 {
+        self.read
+            .wrapping_sub(self.skipped)
+            .wrapping_add(self.padding as u64)
+    }
+
+    /// Number of bytes that have ever been written, not counting padding
+    fn pushed(&self) -> u64 {
+        self.end().wrapping_sub(self.skipped)
+    }
+
+    /// Raise the high-water mark of written bytes to the current end
+    fn track_written(&mut self) {
+        self.written = self.written.max(self.end());
+    }
+
+    /// Wrap a position in `0..2 * capacity` into the storage
+    fn wrap(&self, pos: usize) -> usize {
+        if pos >= self.capacity() {
+            pos - self.capacity()
+        } else {
+            pos
+        }
+    }
+
+    /// Ranges of `buffer` that contain the readable bytes in order
+    fn readable_ranges(&self) -> (Range<usize>, Range<usize>) {
+        let end = self.begin + self.len + self.padding;
+        if end <= self.capacity() {
+            (self.begin..end, 0..0)
+        } else {
+            let limit = self.capacity() - self.padding;
+            (self.begin..limit, 0..end - self.capacity())
+        }
+    }
+
+    /// Ranges of `buffer` that are not readable and not padding in order
+    fn free_ranges(&self) -> (Range<usize>, Range<usize>) {
+        let begin = self.wrap(self.begin + self.len + self.padding);
+        let free = self.capacity() - self.len - self.padding;
+        let end = (begin + free).min(self.capacity());
+        (begin..end, 0..free - (end - begin))
+    }
+
+    /// Ranges of `buffer` that are written to next in order
+    fn writable_ranges(&self) -> (Range<usize>, Range<usize>) {
+        if self.mode == Mode::Overwrite && self.len == self.capacity() {
+            // When full the write position coincides with `begin`, so the
+            // oldest bytes get overwritten.
+            let begin = self.begin;
+            (begin..self.capacity(), 0..begin)
+        } else {
+            self.free_ranges()
+        }
     }
 }
 
-impl Buf for RingBuffer {
+/// Override integer getters of `Buf` to copy straight out of both segments
+macro_rules! get_int {
+    ($($name:ident -> $ty:ty, $from:ident;)*) => {
+        $(
+            fn $name(&mut self) -> $ty {
+                let mut bytes = [0; mem::size_of::<$ty>()];
+                self.copy_to_slice(&mut bytes);
+                <$ty>::$from(bytes)
+            }
+        )*
+    };
+}
+
+/// Override integer putters of `BufMut` to copy straight into both segments
+macro_rules! put_int {
+    ($($name:ident($ty:ty), $to:ident;)*) => {
+        $(
+            fn $name(&mut self, n: $ty) {
+                self.put_slice(&n.$to());
+            }
+        )*
+    };
+}
+
+impl<S: Storage> Buf for RingBuffer<S> {
     fn remaining(&self) -> usize {
         self.len
     }
 
     fn bytes(&self) -> &[u8] {
-        let end = (self.begin + self.len).min(self.capacity());
-        let slice = &self.buffer[self.begin..end];
-        // Safe because `slice` is a subset of the bytes that have been declared
-        // initialized by the unsafe `BufMut::advance_mut` function.
-        unsafe { &*(slice as *const [MaybeUninit<u8>] as *const [u8]) }
+        self.as_slices().0
+    }
+
+    #[cfg(feature = "std")]
+    fn bytes_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        let (first, second) = self.as_slices();
+        let mut cnt = 0;
+        for (dst, slice) in dst
+            .iter_mut()
+            .zip(iter::once(first).chain(iter::once(second)))
+        {
+            if slice.is_empty() {
+                break;
+            }
+            *dst = IoSlice::new(slice);
+            cnt += 1;
+        }
+        cnt
     }
 
     fn advance(&mut self, cnt: usize) {
         assert!(cnt <= self.len);
-        self.begin += cnt;
-        self.begin %= self.capacity();
-        self.len -= cnt;
+        self.consume(cnt);
+    }
+
+    fn copy_to_slice(&mut self, dst: &mut [u8]) {
+        assert!(dst.len() <= self.len);
+        self.peek(0, dst);
+        self.advance(dst.len());
+    }
+
+    get_int! {
+        get_u16 -> u16, from_be_bytes;
+        get_u16_le -> u16, from_le_bytes;
+        get_i16 -> i16, from_be_bytes;
+        get_i16_le -> i16, from_le_bytes;
+        get_u32 -> u32, from_be_bytes;
+        get_u32_le -> u32, from_le_bytes;
+        get_i32 -> i32, from_be_bytes;
+        get_i32_le -> i32, from_le_bytes;
+        get_u64 -> u64, from_be_bytes;
+        get_u64_le -> u64, from_le_bytes;
+        get_i64 -> i64, from_be_bytes;
+        get_i64_le -> i64, from_le_bytes;
+    }
+
+    fn to_bytes(&mut self) -> Bytes {
+        self.split_to(self.len)
     }
 }
 
-impl BufMut for RingBuffer {
+impl<S: Storage> BufMut for RingBuffer<S> {
     fn remaining_mut(&self) -> usize {
-        self.capacity() - self.remaining()
+        match self.mode {
+            Mode::Fixed => self.capacity() - self.len - self.padding,
+            Mode::Overwrite | Mode::Growable => usize::MAX - self.remaining(),
+        }
     }
 
     fn bytes_mut(&mut self) -> &mut [MaybeUninit<u8>] {
-        let begin = (self.begin + self.len) % self.capacity();
-        let end = (begin + self.remaining_mut()).min(self.capacity());
-        &mut self.buffer[begin..end]
+        self.make_writable();
+        let (first, _) = self.writable_ranges();
+        &mut self.buffer.as_mut_slice()[first]
+    }
+
+    #[cfg(feature = "std")]
+    fn bytes_vectored_mut<'a>(&'a mut self, dst: &mut [IoSliceMut<'a>]) -> usize {
+        let mut cnt = 0;
+        let (first, second) = self.free_slices_mut();
+        for (dst, slice) in dst
+            .iter_mut()
+            .zip(iter::once(first).chain(iter::once(second)))
+        {
+            if slice.is_empty() {
+                break;
+            }
+            *dst = IoSliceMut::from(slice);
+            cnt += 1;
+        }
+        cnt
     }
 
     unsafe fn advance_mut(&mut self, cnt: usize) {
-        assert!(cnt <= self.remaining_mut());
-        self.len += cnt;
+        match self.mode {
+            Mode::Fixed | Mode::Growable => {
+                assert!(cnt <= self.capacity() - self.len - self.padding);
+                self.len += cnt;
+            }
+            Mode::Overwrite => {
+                self.unpad();
+                assert!(cnt <= self.capacity());
+                let overflow = (self.len + cnt).saturating_sub(self.capacity());
+                self.begin = self.wrap(self.begin + overflow);
+                self.len += cnt - overflow;
+                self.dropped += overflow as u64;
+            }
+        }
+        self.track_written();
     }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        if let Err(err) = self.try_put_slice(src) {
+            panic!("buffer overflow: {}", err);
+        }
+    }
+
+    put_int! {
+        put_u16(u16), to_be_bytes;
+        put_u16_le(u16), to_le_bytes;
+        put_i16(i16), to_be_bytes;
+        put_i16_le(i16), to_le_bytes;
+        put_u32(u32), to_be_bytes;
+        put_u32_le(u32), to_le_bytes;
+        put_i32(i32), to_be_bytes;
+        put_i32_le(i32), to_le_bytes;
+        put_u64(u64), to_be_bytes;
+        put_u64_le(u64), to_le_bytes;
+        put_i64(i64), to_be_bytes;
+        put_i64_le(i64), to_le_bytes;
+    }
+}
+
+/// Write `data` to the empty `buf` so that it wraps around the end of the
+/// storage after `split` bytes
+#[cfg(all(test, feature = "alloc"))]
+pub(crate) fn wrapped<S: Storage>(
+    mut buf: RingBuffer<S>,
+    split: usize,
+    data: &[u8],
+) -> RingBuffer<S> {
+    for _ in split..buf.capacity() {
+        buf.put_u8(0);
+        buf.advance(1);
+    }
+    buf.put_slice(data);
+    buf
 }
 
 #[cfg(test)]
@@ -85,6 +681,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn ringbuffer_init() {
         let buf = RingBuffer::new(16);
         assert_eq!(buf.capacity(), 16);
@@ -93,6 +690,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn ringbuffer_read_write() {
         let mut buf = RingBuffer::new(16);
 
@@ -128,6 +726,7 @@ mod tests {
 
     #[test]
     #[should_panic]
+    #[cfg(feature = "alloc")]
     fn ringbuffer_read_over_remaining() {
         let mut buf = RingBuffer::new(16);
         for i in 0..15 {
@@ -140,10 +739,163 @@ mod tests {
 
     #[test]
     #[should_panic]
+    #[cfg(feature = "alloc")]
     fn ringbuffer_write_over_capacity() {
         let mut buf = RingBuffer::new(16);
         for i in 0..17 {
             buf.put_u8(i);
         }
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_overwrite() {
+        let mut buf = RingBuffer::with_overwrite(4);
+        assert!(buf.is_overwrite());
+        buf.put_slice(&[0, 1, 2]);
+        assert_eq!(buf.dropped(), 0);
+
+        // Evict the two oldest bytes
+        buf.put_slice(&[3, 4, 5]);
+        assert_eq!(buf.remaining(), 4);
+        assert_eq!(buf.dropped(), 2);
+        assert_eq!(buf.get_u8(), 2);
+
+        // Write more than the capacity at once
+        buf.put_slice(&[6, 7, 8, 9, 10, 11]);
+        assert_eq!(buf.remaining(), 4);
+        assert_eq!(buf.dropped(), 7);
+        for i in 8..12 {
+            assert_eq!(buf.get_u8(), i);
+        }
+        assert_eq!(buf.remaining(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_growable() {
+        let mut buf = RingBuffer::growable(4);
+        assert!(buf.is_growable());
+        buf.put_slice(&[0, 1, 2]);
+        buf.advance(2);
+
+        // Grow while the readable bytes wrap around the end of the storage
+        buf.put_slice(&[3, 4, 5, 6, 7]);
+        assert!(buf.capacity() >= 6);
+        assert_eq!(buf.bytes(), &[2, 3, 4, 5, 6, 7]);
+
+        buf.put_slice(&[0; 100]);
+        assert_eq!(buf.remaining(), 106);
+        for i in 2..8 {
+            assert_eq!(buf.get_u8(), i);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_reserve() {
+        let mut buf = RingBuffer::new(4);
+        buf.put_slice(&[0, 1, 2]);
+        buf.advance(2);
+        buf.put_slice(&[3, 4]);
+
+        buf.reserve(1);
+        assert_eq!(buf.capacity(), 4);
+        buf.reserve(2);
+        assert_eq!(buf.capacity(), 8);
+        assert_eq!(buf.bytes(), &[2, 3, 4]);
+        assert_eq!(buf.remaining_mut(), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_split_to() {
+        let mut buf = wrapped(RingBuffer::new(8), 2, &[0, 1, 2, 3, 4]);
+        assert_eq!(&buf.split_to(1)[..], &[0]);
+        assert_eq!(&buf.split_to(3)[..], &[1, 2, 3]);
+        buf.put_slice(&[5, 6]);
+        assert_eq!(&buf.to_bytes()[..], &[4, 5, 6]);
+        assert_eq!(buf.remaining(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_int_across_wrap() {
+        for offset in 0..8 {
+            let mut buf = wrapped(RingBuffer::new(8), 8 - offset, &[]);
+            buf.put_u64(0x0102_0304_0506_0708);
+            assert_eq!(buf.get_u32_le(), 0x0403_0201);
+            buf.put_i16(-2);
+            buf.put_u16_le(0x0a09);
+            assert_eq!(buf.get_u64(), 0x0506_0708_fffe_090a);
+
+            let mut dst = [0; 3];
+            buf.put_slice(&[1, 2, 3]);
+            buf.copy_to_slice(&mut dst);
+            assert_eq!(dst, [1, 2, 3]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_make_contiguous() {
+        let mut buf = wrapped(RingBuffer::new(8), 2, &[0, 1, 2, 3, 4]);
+        assert_eq!(buf.as_slices(), (&[0, 1][..], &[2, 3, 4][..]));
+
+        assert_eq!(buf.make_contiguous(), &[0, 1, 2, 3, 4]);
+        assert_eq!(buf.as_slices(), (&[0, 1, 2, 3, 4][..], &[][..]));
+        assert_eq!(buf.remaining_mut(), 3);
+        buf.put_slice(&[5, 6, 7]);
+        assert_eq!(buf.bytes(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn ringbuffer_array() {
+        let mut buf = ArrayRingBuffer::<4>::default();
+        assert_eq!(buf.capacity(), 4);
+        buf.put_slice(&[0, 1, 2]);
+        buf.advance(2);
+        buf.put_slice(&[3, 4, 5]);
+        assert_eq!(buf.remaining_mut(), 0);
+        assert_eq!(buf.as_slices(), (&[2, 3][..], &[4, 5][..]));
+
+        let mut buf = RingBuffer::from_storage_with_overwrite([MaybeUninit::uninit(); 4]);
+        buf.put_slice(&[0, 1, 2, 3, 4, 5]);
+        assert_eq!(buf.dropped(), 2);
+        assert_eq!(buf.get_u32(), 0x0203_0405);
+    }
+
+    #[test]
+    #[should_panic]
+    fn ringbuffer_array_reserve() {
+        let mut buf = ArrayRingBuffer::<4>::default();
+        buf.reserve(5);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ringbuffer_vectored() {
+        let mut buf = RingBuffer::new(8);
+        buf.put_slice(&[0, 1, 2, 3, 4, 5]);
+        buf.advance(4);
+
+        // Free space wraps around the end of the storage
+        let (first, second) = buf.free_slices_mut();
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 4);
+        let (mut a, mut b) = ([0u8; 0], [0u8; 0]);
+        let mut dst = [IoSliceMut::from(&mut a[..]), IoSliceMut::from(&mut b[..])];
+        assert_eq!(buf.bytes_vectored_mut(&mut dst), 2);
+
+        buf.put_slice(&[6, 7, 8, 9]);
+        // Readable bytes wrap around the end of the storage
+        let mut dst = [IoSlice::new(&[]); 3];
+        assert_eq!(buf.bytes_vectored(&mut dst), 2);
+        assert_eq!(&*dst[0], &[4, 5, 6, 7]);
+        assert_eq!(&*dst[1], &[8, 9]);
+
+        buf.advance(6);
+        let mut dst = [IoSlice::new(&[]); 3];
+        assert_eq!(buf.bytes_vectored(&mut dst), 0);
+    }
 }
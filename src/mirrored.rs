@@ -0,0 +1,171 @@
+//! Ringbuffer that maps its memory twice so that no data wraps
+use core::mem::MaybeUninit;
+use core::{ptr, slice};
+use std::io;
+
+use super::{Buf, BufMut};
+
+/// Fixed-capacity ringbuffer whose memory is mapped twice, back to back
+///
+/// Because the byte after the end of the storage is the first byte of the
+/// storage again, `bytes()` always returns all readable bytes and `bytes_mut()`
+/// always returns all free bytes as a single slice.
+///
+/// Only available on Linux.
+#[derive(Debug)]
+pub struct MirroredRingBuffer {
+    ptr: *mut u8,
+    capacity: usize,
+    begin: usize,
+    len: usize,
+}
+
+// Safe because the mapping is owned by the ringbuffer and only accessed
+// through `&self` or `&mut self`.
+unsafe impl Send for MirroredRingBuffer {}
+unsafe impl Sync for MirroredRingBuffer {}
+
+impl MirroredRingBuffer {
+    /// Create a ringbuffer with at least the given capacity
+    ///
+    /// The capacity is rounded up to a non-zero multiple of the page size.
+    pub fn new(capacity: usize) -> io::Result<Self> {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let capacity = capacity.max(1).div_ceil(page_size) * page_size;
+        let ptr = unsafe { map_mirrored(capacity)? };
+        Ok(Self {
+            ptr,
+            capacity,
+            begin: 0,
+            len: 0,
+        })
+    }
+
+    /// Capacity of the ringbuffer
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// Map a memory file of `size` bytes twice into contiguous address space
+unsafe fn map_mirrored(size: usize) -> io::Result<*mut u8> {
+    let fd = libc::memfd_create(
+        b"bytes-ringbuffer\0".as_ptr() as *const libc::c_char,
+        libc::MFD_CLOEXEC,
+    );
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let result = map_file_mirrored(fd, size);
+    // The mappings keep the memory file alive
+    libc::close(fd);
+    result
+}
+
+unsafe fn map_file_mirrored(fd: libc::c_int, size: usize) -> io::Result<*mut u8> {
+    if libc::ftruncate(fd, size as libc::off_t) < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Reserve the address space for both mappings
+    let base = libc::mmap(
+        ptr::null_mut(),
+        2 * size,
+        libc::PROT_NONE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+        -1,
+        0,
+    );
+    if base == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    for offset in [0, size].iter() {
+        let addr = libc::mmap(
+            (base as *mut u8).add(*offset) as *mut libc::c_void,
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            fd,
+            0,
+        );
+        if addr == libc::MAP_FAILED {
+            let err = io::Error::last_os_error();
+            libc::munmap(base, 2 * size);
+            return Err(err);
+        }
+    }
+    Ok(base as *mut u8)
+}
+
+impl Drop for MirroredRingBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, 2 * self.capacity);
+        }
+    }
+}
+
+impl Buf for MirroredRingBuffer {
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // Safe because the readable bytes have been declared initialized by
+        // `BufMut::advance_mut` and the mirror covers the part that wraps.
+        unsafe { slice::from_raw_parts(self.ptr.add(self.begin), self.len) }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.len);
+        self.begin += cnt;
+        self.begin %= self.capacity;
+        self.len -= cnt;
+    }
+}
+
+impl BufMut for MirroredRingBuffer {
+    fn remaining_mut(&self) -> usize {
+        self.capacity - self.len
+    }
+
+    fn bytes_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        let begin = (self.begin + self.len) % self.capacity;
+        // Safe because the mirror covers the part of the free space that wraps.
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.ptr.add(begin) as *mut MaybeUninit<u8>,
+                self.remaining_mut(),
+            )
+        }
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining_mut());
+        self.len += cnt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrored_ringbuffer() {
+        let mut buf = MirroredRingBuffer::new(1).unwrap();
+        let capacity = buf.capacity();
+        assert!(capacity > 0);
+        assert_eq!(buf.remaining_mut(), capacity);
+
+        buf.put_slice(&vec![0; capacity - 2]);
+        buf.advance(capacity - 2);
+        // Both the free space and the readable bytes wrap
+        assert_eq!(buf.bytes_mut().len(), capacity);
+        buf.put_slice(&[0, 1, 2, 3, 4]);
+        assert_eq!(buf.bytes(), &[0, 1, 2, 3, 4]);
+        assert_eq!(buf.bytes_mut().len(), capacity - 5);
+        for i in 0..5 {
+            assert_eq!(buf.get_u8(), i);
+        }
+        assert_eq!(buf.remaining(), 0);
+    }
+}
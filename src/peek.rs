@@ -0,0 +1,95 @@
+//! Non-consuming reads at logical offsets
+use core::mem;
+
+use super::{RingBuffer, Storage};
+
+macro_rules! peek_int {
+    ($name:ident, $ty:ty, $from:ident, $order:expr) => {
+        #[doc = concat!("Read a ", $order, " `", stringify!($ty), "` at `offset` without consuming it")]
+        ///
+        /// Returns `None` if fewer bytes are readable.
+        pub fn $name(&self, offset: usize) -> Option<$ty> {
+            let mut bytes = [0; mem::size_of::<$ty>()];
+            if self.peek(offset, &mut bytes) == bytes.len() {
+                Some(<$ty>::$from(bytes))
+            } else {
+                None
+            }
+        }
+    };
+}
+
+impl<S: Storage> RingBuffer<S> {
+    /// Copy readable bytes starting at `offset` into `dst` without consuming them
+    ///
+    /// `offset` is relative to the first readable byte. Returns the number of
+    /// bytes copied, which is less than `dst.len()` if not enough bytes are
+    /// readable.
+    pub fn peek(&self, offset: usize, dst: &mut [u8]) -> usize {
+        let (first, second) = self.as_slices();
+        let mut offset = offset;
+        let mut cnt = 0;
+        for slice in [first, second].iter() {
+            if offset >= slice.len() {
+                offset -= slice.len();
+                continue;
+            }
+            let n = (slice.len() - offset).min(dst.len() - cnt);
+            dst[cnt..cnt + n].copy_from_slice(&slice[offset..offset + n]);
+            offset = 0;
+            cnt += n;
+        }
+        cnt
+    }
+
+    /// Readable byte at `index` relative to the first readable byte
+    pub fn get(&self, index: usize) -> Option<u8> {
+        let (first, second) = self.as_slices();
+        match first.get(index) {
+            Some(byte) => Some(*byte),
+            None => second.get(index - first.len()).copied(),
+        }
+    }
+
+    peek_int!(peek_u16, u16, from_be_bytes, "big-endian");
+    peek_int!(peek_u16_le, u16, from_le_bytes, "little-endian");
+    peek_int!(peek_u32, u32, from_be_bytes, "big-endian");
+    peek_int!(peek_u32_le, u32, from_le_bytes, "little-endian");
+    peek_int!(peek_u64, u64, from_be_bytes, "big-endian");
+    peek_int!(peek_u64_le, u64, from_le_bytes, "little-endian");
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{wrapped, Buf};
+
+    #[test]
+    fn ringbuffer_peek() {
+        let buf = wrapped(RingBuffer::new(10), 3, &[0, 1, 2, 3, 4, 5, 6, 7]);
+        let mut dst = [0; 4];
+        assert_eq!(buf.peek(1, &mut dst), 4);
+        assert_eq!(dst, [1, 2, 3, 4]);
+        assert_eq!(buf.peek(6, &mut dst), 2);
+        assert_eq!(dst[..2], [6, 7]);
+        assert_eq!(buf.peek(8, &mut dst), 0);
+        assert_eq!(buf.remaining(), 8);
+
+        assert_eq!(buf.get(2), Some(2));
+        assert_eq!(buf.get(3), Some(3));
+        assert_eq!(buf.get(8), None);
+    }
+
+    #[test]
+    fn ringbuffer_peek_int() {
+        let buf = wrapped(RingBuffer::new(10), 3, &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(buf.peek_u16(2), Some(0x0203));
+        assert_eq!(buf.peek_u16_le(2), Some(0x0302));
+        assert_eq!(buf.peek_u32(1), Some(0x0102_0304));
+        assert_eq!(buf.peek_u32_le(1), Some(0x0403_0201));
+        assert_eq!(buf.peek_u64(0), Some(0x0001_0203_0405_0607));
+        assert_eq!(buf.peek_u64_le(0), Some(0x0706_0504_0302_0100));
+        assert_eq!(buf.peek_u64(1), None);
+        assert_eq!(buf.remaining(), 8);
+    }
+}
@@ -0,0 +1,276 @@
+//! In-memory async byte pipe backed by a fixed-size [RingBuffer](../struct.RingBuffer.html)
+//!
+//! [pipe](fn.pipe.html) returns a [Writer](struct.Writer.html) and a
+//! [Reader](struct.Reader.html) that implement the `AsyncWrite` and `AsyncRead`
+//! traits of futures-io and work with any executor. The writer is suspended
+//! while the buffer is full and the reader while it is empty. Once the writer
+//! is closed or dropped the reader returns the remaining bytes followed by EOF.
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+use super::{Buf, BufMut, RingBuffer};
+
+/// State shared between a `Writer` and a `Reader`
+#[derive(Debug)]
+struct Pipe {
+    buf: RingBuffer,
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+    write_closed: bool,
+    read_closed: bool,
+}
+
+/// Writing half of a [pipe](fn.pipe.html)
+#[derive(Debug)]
+pub struct Writer {
+    pipe: Arc<Mutex<Pipe>>,
+}
+
+/// Reading half of a [pipe](fn.pipe.html)
+#[derive(Debug)]
+pub struct Reader {
+    pipe: Arc<Mutex<Pipe>>,
+}
+
+/// Create a pipe that buffers up to `capacity` bytes
+pub fn pipe(capacity: usize) -> (Writer, Reader) {
+    let pipe = Arc::new(Mutex::new(Pipe {
+        buf: RingBuffer::new(capacity),
+        reader: None,
+        writer: None,
+        write_closed: false,
+        read_closed: false,
+    }));
+    (Writer { pipe: pipe.clone() }, Reader { pipe })
+}
+
+fn lock(pipe: &Mutex<Pipe>) -> MutexGuard<'_, Pipe> {
+    pipe.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Remember the waker of `cx` to be woken up later
+fn register(slot: &mut Option<Waker>, cx: &Context<'_>) {
+    match slot {
+        Some(waker) if waker.will_wake(cx.waker()) => {}
+        _ => *slot = Some(cx.waker().clone()),
+    }
+}
+
+fn wake(slot: &mut Option<Waker>) {
+    if let Some(waker) = slot.take() {
+        waker.wake();
+    }
+}
+
+impl Writer {
+    /// Capacity of the pipe
+    pub fn capacity(&self) -> usize {
+        lock(&self.pipe).buf.capacity()
+    }
+
+    fn close(&self) {
+        let mut pipe = lock(&self.pipe);
+        pipe.write_closed = true;
+        wake(&mut pipe.reader);
+    }
+}
+
+impl Reader {
+    /// Capacity of the pipe
+    pub fn capacity(&self) -> usize {
+        lock(&self.pipe).buf.capacity()
+    }
+}
+
+impl AsyncWrite for Writer {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut pipe = lock(&self.pipe);
+        if pipe.read_closed || pipe.write_closed {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if src.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let cnt = src.len().min(pipe.buf.remaining_mut());
+        if cnt == 0 {
+            register(&mut pipe.writer, cx);
+            return Poll::Pending;
+        }
+        pipe.buf.put_slice(&src[..cnt]);
+        wake(&mut pipe.reader);
+        Poll::Ready(Ok(cnt))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for Reader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut pipe = lock(&self.pipe);
+        if dst.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let cnt = dst.len().min(pipe.buf.remaining());
+        if cnt == 0 {
+            if pipe.write_closed {
+                return Poll::Ready(Ok(0));
+            }
+            register(&mut pipe.reader, cx);
+            return Poll::Pending;
+        }
+        pipe.buf.copy_to_slice(&mut dst[..cnt]);
+        wake(&mut pipe.writer);
+        Poll::Ready(Ok(cnt))
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        let mut pipe = lock(&self.pipe);
+        pipe.read_closed = true;
+        wake(&mut pipe.writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Wake;
+
+    /// Waker that counts how often it has been woken up
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl Counter {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    fn waker() -> (Arc<Counter>, Waker) {
+        let counter = Arc::new(Counter::default());
+        (counter.clone(), counter.into())
+    }
+
+    fn write(writer: &mut Writer, waker: &Waker, src: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(writer).poll_write(&mut Context::from_waker(waker), src)
+    }
+
+    fn read(reader: &mut Reader, waker: &Waker, dst: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(reader).poll_read(&mut Context::from_waker(waker), dst)
+    }
+
+    #[test]
+    fn pipe_backpressure() {
+        let (mut writer, mut reader) = pipe(4);
+        let (writes, write_waker) = waker();
+        let (reads, read_waker) = waker();
+        let mut dst = [0; 8];
+
+        assert!(read(&mut reader, &read_waker, &mut dst).is_pending());
+        assert!(matches!(
+            write(&mut writer, &write_waker, &[1, 2, 3, 4, 5, 6]),
+            Poll::Ready(Ok(4))
+        ));
+        assert_eq!(reads.count(), 1);
+        assert!(write(&mut writer, &write_waker, &[5, 6]).is_pending());
+
+        assert!(matches!(
+            read(&mut reader, &read_waker, &mut dst[..3]),
+            Poll::Ready(Ok(3))
+        ));
+        assert_eq!(dst[..3], [1, 2, 3]);
+        assert_eq!(writes.count(), 1);
+        assert!(matches!(
+            write(&mut writer, &write_waker, &[5, 6]),
+            Poll::Ready(Ok(2))
+        ));
+        assert!(matches!(
+            read(&mut reader, &read_waker, &mut dst),
+            Poll::Ready(Ok(3))
+        ));
+        assert_eq!(dst[..3], [4, 5, 6]);
+    }
+
+    #[test]
+    fn pipe_eof() {
+        let (mut writer, mut reader) = pipe(4);
+        let (reads, read_waker) = waker();
+        let (_, write_waker) = waker();
+        let mut dst = [0; 4];
+
+        assert!(matches!(
+            write(&mut writer, &write_waker, &[1, 2]),
+            Poll::Ready(Ok(2))
+        ));
+        drop(writer);
+        assert_eq!(reads.count(), 0);
+        assert!(matches!(
+            read(&mut reader, &read_waker, &mut dst),
+            Poll::Ready(Ok(2))
+        ));
+        assert!(matches!(
+            read(&mut reader, &read_waker, &mut dst),
+            Poll::Ready(Ok(0))
+        ));
+
+        // A pending reader is woken up when the writer goes away
+        let (writer, mut reader) = pipe(4);
+        assert!(read(&mut reader, &read_waker, &mut dst).is_pending());
+        drop(writer);
+        assert_eq!(reads.count(), 1);
+        assert!(matches!(
+            read(&mut reader, &read_waker, &mut dst),
+            Poll::Ready(Ok(0))
+        ));
+    }
+
+    #[test]
+    fn pipe_broken() {
+        let (mut writer, reader) = pipe(2);
+        let (writes, write_waker) = waker();
+        assert!(matches!(
+            write(&mut writer, &write_waker, &[1, 2, 3]),
+            Poll::Ready(Ok(2))
+        ));
+        assert!(write(&mut writer, &write_waker, &[3]).is_pending());
+        drop(reader);
+        assert_eq!(writes.count(), 1);
+        match write(&mut writer, &write_waker, &[3]) {
+            Poll::Ready(Err(err)) => assert_eq!(err.kind(), io::ErrorKind::BrokenPipe),
+            _ => panic!("write after the reader is gone"),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Ringbuffer of variable-length records that are each stored contiguously
+use core::convert::TryFrom;
+use core::mem::{self, MaybeUninit};
+
+#[cfg(feature = "alloc")]
+use super::DefaultStorage;
+use super::{Buf, BufMut, CapacityError, RingBuffer, Storage};
+
+/// Length of the big-endian `u32` that precedes every record
+const HEADER: usize = 4;
+
+default_storage! {
+    /// Ringbuffer that stores discrete records instead of a byte stream
+    ///
+    /// Every record is stored behind a 4 byte length header. A record that does
+    /// not fit in front of the end of the storage is written to its start and
+    /// the end is skipped, so records never wrap and can be borrowed as a single
+    /// slice.
+    #[derive(Debug)]
+    pub struct RecordRing<S> {
+        buf: RingBuffer<S>,
+        count: usize,
+    }
+}
+
+/// Iterator over the records of a [RecordRing](struct.RecordRing.html) returned
+/// by [iter](struct.RecordRing.html#method.iter)
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    first: &'a [u8],
+    second: &'a [u8],
+}
+
+#[cfg(feature = "alloc")]
+impl RecordRing {
+    /// Create a record ring with the given capacity in bytes, headers included
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer::new(capacity).into()
+    }
+}
+
+impl<S: Storage> RecordRing<S> {
+    /// Create an empty record ring that uses `storage` as its memory
+    pub const fn from_storage(storage: S) -> Self {
+        Self {
+            buf: RingBuffer::from_storage(storage),
+            count: 0,
+        }
+    }
+
+    /// Capacity of the ringbuffer in bytes, headers included
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Number of stored records
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether no record is stored
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Append a record
+    ///
+    /// Returns an error if the record and its header do not fit contiguously
+    /// into the free space.
+    pub fn push_record(&mut self, record: &[u8]) -> Result<(), CapacityError> {
+        let header = u32::try_from(record.len())
+            .map_err(|_| CapacityError::new(record.len(), u32::MAX as usize))?;
+        let mut grant = self.buf.grant_exact(HEADER + record.len())?;
+        grant.put_u32(header);
+        grant.put_slice(record);
+        grant.commit(HEADER + record.len());
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Remove the oldest record and return it
+    ///
+    /// The record stays valid until the ring is modified again.
+    pub fn pop_record(&mut self) -> Option<&[u8]> {
+        let len = self.peek_record()?.len();
+        let start = self.buf.readable_ranges().0.start + HEADER;
+        self.buf.advance(HEADER + len);
+        self.count -= 1;
+        let record = &self.buf.buffer.as_slice()[start..start + len];
+        // Safe because the bytes were readable until just now and cannot be
+        // written while the returned slice borrows the ring.
+        Some(unsafe { &*(record as *const [MaybeUninit<u8>] as *const [u8]) })
+    }
+
+    /// Oldest record without removing it
+    pub fn peek_record(&self) -> Option<&[u8]> {
+        self.iter().next()
+    }
+
+    /// Iterate over the stored records from oldest to newest without removing
+    /// them
+    pub fn iter(&self) -> Records<'_> {
+        let (first, second) = self.buf.as_slices();
+        Records { first, second }
+    }
+}
+
+impl<S: Storage> From<RingBuffer<S>> for RecordRing<S> {
+    /// Use the storage of `buf`, which has to be empty
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` has readable bytes.
+    fn from(buf: RingBuffer<S>) -> Self {
+        assert!(!buf.has_remaining(), "ringbuffer is not empty");
+        Self { buf, count: 0 }
+    }
+}
+
+impl<'a, S: Storage> IntoIterator for &'a RecordRing<S> {
+    type Item = &'a [u8];
+    type IntoIter = Records<'a>;
+
+    fn into_iter(self) -> Records<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        // Records never cross from one segment into the other
+        if self.first.is_empty() {
+            mem::swap(&mut self.first, &mut self.second);
+        }
+        if self.first.is_empty() {
+            return None;
+        }
+        let (header, rest) = self.first.split_at(HEADER);
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let (record, rest) = rest.split_at(len);
+        self.first = rest;
+        Some(record)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn record_ring_push_pop() {
+        let mut ring = RecordRing::new(16);
+        ring.push_record(&[1, 2, 3, 4, 5]).unwrap();
+        ring.push_record(&[6, 7, 8]).unwrap();
+        assert_eq!(ring.push_record(&[]), Err(CapacityError::new(4, 0)));
+        assert_eq!(ring.pop_record(), Some(&[1, 2, 3, 4, 5][..]));
+
+        // Records at the end and at the start of the storage
+        ring.push_record(&[9, 10, 11, 12]).unwrap();
+        let records: Vec<_> = ring.iter().collect();
+        assert_eq!(records, [&[6, 7, 8][..], &[9, 10, 11, 12][..]]);
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.pop_record(), Some(&[6, 7, 8][..]));
+        assert_eq!(ring.push_record(&[0; 5]), Err(CapacityError::new(9, 8)));
+        ring.push_record(&[13, 14]).unwrap();
+        assert_eq!(ring.pop_record(), Some(&[9, 10, 11, 12][..]));
+        assert_eq!(ring.pop_record(), Some(&[13, 14][..]));
+        assert_eq!(ring.pop_record(), None);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn record_ring_skip_end() {
+        let mut ring = RecordRing::new(16);
+        ring.push_record(&[0; 4]).unwrap();
+        ring.push_record(&[1, 2]).unwrap();
+        ring.pop_record().unwrap();
+
+        // Only 2 bytes are left at the end, so the record goes to the start
+        ring.push_record(&[3, 4, 5]).unwrap();
+        assert_eq!(ring.peek_record(), Some(&[1, 2][..]));
+        let records: Vec<_> = (&ring).into_iter().collect();
+        assert_eq!(records, [&[1, 2][..], &[3, 4, 5][..]]);
+        assert_eq!(ring.pop_record(), Some(&[1, 2][..]));
+        assert_eq!(ring.pop_record(), Some(&[3, 4, 5][..]));
+        assert_eq!(ring.pop_record(), None);
+
+        // An empty ring starts over at the start of the storage
+        ring.push_record(&[0; 12]).unwrap();
+        assert_eq!(ring.pop_record(), Some(&[0; 12][..]));
+    }
+}
@@ -0,0 +1,111 @@
+//! Searching the readable bytes for delimiters
+use bytes::{Buf, Bytes};
+
+use super::{RingBuffer, Storage};
+
+impl<S: Storage> RingBuffer<S> {
+    /// Offset of the first occurrence of `byte` in the readable bytes
+    ///
+    /// The offset is relative to the first readable byte.
+    pub fn find_byte(&self, byte: u8) -> Option<usize> {
+        let (first, second) = self.as_slices();
+        memchr::memchr(byte, first)
+            .or_else(|| memchr::memchr(byte, second).map(|pos| first.len() + pos))
+    }
+
+    /// Offset of the first occurrence of `needle` in the readable bytes
+    ///
+    /// The offset is relative to the first readable byte. Matches that wrap
+    /// around the end of the storage are found as well.
+    pub fn find(&self, needle: &[u8]) -> Option<usize> {
+        let (first, second) = self.as_slices();
+        if let Some(pos) = memchr::memmem::find(first, needle) {
+            return Some(pos);
+        }
+        // Candidates that start in `first` and end in `second`
+        let start = (first.len() + 1).saturating_sub(needle.len());
+        for pos in start..first.len() {
+            let split = first.len() - pos;
+            if first[pos..] == needle[..split] && second.starts_with(&needle[split..]) {
+                return Some(pos);
+            }
+        }
+        memchr::memmem::find(second, needle).map(|pos| first.len() + pos)
+    }
+
+    /// Remove the bytes up to the first occurrence of `delimiter`
+    ///
+    /// The delimiter is consumed as well but is not part of the returned bytes.
+    /// Returns `None` without consuming anything if `delimiter` is not readable.
+    pub fn split_to_delimiter(&mut self, delimiter: &[u8]) -> Option<Bytes> {
+        let pos = self.find(delimiter)?;
+        let bytes = self.split_to(pos);
+        self.advance(delimiter.len());
+        Some(bytes)
+    }
+
+    /// Remove the next line terminated by `\n` or `\r\n`
+    ///
+    /// The terminator is consumed as well but is not part of the returned
+    /// bytes. Returns `None` without consuming anything if no complete line is
+    /// readable.
+    pub fn split_line(&mut self) -> Option<Bytes> {
+        let pos = self.find_byte(b'\n')?;
+        let mut line = self.split_to(pos);
+        self.advance(1);
+        if line.ends_with(b"\r") {
+            line.truncate(line.len() - 1);
+        }
+        Some(line)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::wrapped;
+
+    #[test]
+    fn ringbuffer_find_byte() {
+        for split in 1..=6 {
+            let buf = wrapped(RingBuffer::new(10), split, b"abcdef");
+            assert_eq!(buf.find_byte(b'a'), Some(0));
+            assert_eq!(buf.find_byte(b'c'), Some(2));
+            assert_eq!(buf.find_byte(b'f'), Some(5));
+            assert_eq!(buf.find_byte(b'x'), None);
+        }
+    }
+
+    #[test]
+    fn ringbuffer_find() {
+        for split in 1..=8 {
+            let buf = wrapped(RingBuffer::new(12), split, b"ab\r\ncd\r\n");
+            assert_eq!(buf.find(b"\r\n"), Some(2));
+            assert_eq!(buf.find(b"b\r\nc"), Some(1));
+            assert_eq!(buf.find(b"d\r\n"), Some(5));
+            assert_eq!(buf.find(b"ab\r\ncd\r\n"), Some(0));
+            assert_eq!(buf.find(b"ab\r\ncd\r\n\r\n"), None);
+            assert_eq!(buf.find(b"\n\n"), None);
+            assert_eq!(buf.find(b""), Some(0));
+        }
+    }
+
+    #[test]
+    fn ringbuffer_split_to_delimiter() {
+        let mut buf = wrapped(RingBuffer::new(22), 5, b"key: value\r\n\r\nbody");
+        assert_eq!(&buf.split_to_delimiter(b": ").unwrap()[..], b"key");
+        assert_eq!(&buf.split_to_delimiter(b"\r\n").unwrap()[..], b"value");
+        assert_eq!(&buf.split_to_delimiter(b"\r\n").unwrap()[..], b"");
+        assert_eq!(buf.split_to_delimiter(b"\r\n"), None);
+        assert_eq!(buf.bytes(), b"body");
+    }
+
+    #[test]
+    fn ringbuffer_split_line() {
+        let mut buf = wrapped(RingBuffer::new(21), 3, b"PING\r\nSET a 1\nGET");
+        assert_eq!(&buf.split_line().unwrap()[..], b"PING");
+        assert_eq!(&buf.split_line().unwrap()[..], b"SET a 1");
+        assert_eq!(buf.split_line(), None);
+        assert_eq!(buf.remaining(), 3);
+    }
+}
@@ -0,0 +1,212 @@
+//! Serialization of the capacity and the readable bytes of a `RingBuffer`
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use bytes::BufMut;
+#[cfg(feature = "alloc")]
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use super::{Mode, RingBuffer, Storage};
+
+/// Fields of a serialized ringbuffer
+#[derive(serde::Serialize)]
+#[serde(rename = "RingBuffer")]
+struct Snapshot<'a> {
+    capacity: usize,
+    mode: Mode,
+    data: Segments<'a>,
+}
+
+/// Readable bytes in both segments, serialized as a single sequence of `u8`
+///
+/// A byte string would need the segments to be contiguous, which requires a
+/// copy and thus `alloc`. The same shape is emitted either way so that the
+/// format does not depend on cargo features.
+struct Segments<'a>(&'a [u8], &'a [u8]);
+
+impl<'a> Serialize for Segments<'a> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let Segments(first, second) = self;
+        let mut seq = serializer.serialize_seq(Some(first.len() + second.len()))?;
+        for byte in first.iter().chain(second.iter()) {
+            seq.serialize_element(byte)?;
+        }
+        seq.end()
+    }
+}
+
+impl<S: Storage> Serialize for RingBuffer<S> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let (first, second) = self.as_slices();
+        Snapshot {
+            capacity: self.capacity(),
+            mode: self.mode,
+            data: Segments(first, second),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Fields of a deserialized ringbuffer
+#[cfg(feature = "alloc")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "RingBuffer")]
+struct OwnedSnapshot {
+    capacity: usize,
+    mode: Mode,
+    data: Data,
+}
+
+/// Readable bytes that are accepted as a byte string or a sequence
+#[cfg(feature = "alloc")]
+struct Data(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for Data {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DataVisitor;
+
+        impl<'de> Visitor<'de> for DataVisitor {
+            type Value = Data;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a sequence of bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Data, E> {
+                Ok(Data(v.to_vec()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Data, A::Error> {
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element()? {
+                    data.push(byte);
+                }
+                Ok(Data(data))
+            }
+        }
+
+        deserializer.deserialize_seq(DataVisitor)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for RingBuffer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = OwnedSnapshot::deserialize(deserializer)?;
+        let data = snapshot.data.0;
+        if data.len() > snapshot.capacity {
+            return Err(de::Error::invalid_length(
+                data.len(),
+                &"at most capacity bytes",
+            ));
+        }
+        let mut buf = RingBuffer::try_new(snapshot.capacity).map_err(de::Error::custom)?;
+        buf.mode = snapshot.mode;
+        buf.put_slice(&data);
+        Ok(buf)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{wrapped, Buf};
+    use alloc::vec;
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Token};
+
+    fn tokens(capacity: u64, variant: &'static str, data: Vec<Token>) -> Vec<Token> {
+        let mut tokens = vec![
+            Token::Struct {
+                name: "RingBuffer",
+                len: 3,
+            },
+            Token::Str("capacity"),
+            Token::U64(capacity),
+            Token::Str("mode"),
+            Token::UnitVariant {
+                name: "Mode",
+                variant,
+            },
+            Token::Str("data"),
+        ];
+        tokens.extend(data);
+        tokens.push(Token::StructEnd);
+        tokens
+    }
+
+    /// Ringbuffer that compares equal to another one with the same capacity,
+    /// mode and readable bytes
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(transparent)]
+    struct Contents(RingBuffer);
+
+    impl PartialEq for Contents {
+        fn eq(&self, other: &Self) -> bool {
+            let (a, b) = (&self.0, &other.0);
+            a.capacity() == b.capacity() && a.mode == b.mode && a.as_slices() == b.as_slices()
+        }
+    }
+
+    fn seq(data: &[u8]) -> Vec<Token> {
+        let mut tokens = vec![Token::Seq {
+            len: Some(data.len()),
+        }];
+        tokens.extend(data.iter().map(|byte| Token::U8(*byte)));
+        tokens.push(Token::SeqEnd);
+        tokens
+    }
+
+    #[test]
+    fn ringbuffer_serialize() {
+        // The same shape whether the readable bytes wrap or not
+        let expected = tokens(4, "Overwrite", seq(&[2, 3, 4]));
+        assert_ser_tokens(
+            &wrapped(RingBuffer::with_overwrite(4), 2, &[2, 3, 4]),
+            &expected,
+        );
+        let mut buf = RingBuffer::with_overwrite(4);
+        buf.put_slice(&[2, 3, 4]);
+        assert_ser_tokens(&buf, &expected);
+        assert_ser_tokens(&RingBuffer::growable(8), &tokens(8, "Growable", seq(&[])));
+    }
+
+    #[test]
+    fn ringbuffer_deserialize() {
+        let json =
+            serde_json::to_string(&wrapped(RingBuffer::with_overwrite(4), 2, &[2, 3, 4])).unwrap();
+        assert_eq!(json, r#"{"capacity":4,"mode":"Overwrite","data":[2,3,4]}"#);
+        let mut buf: RingBuffer = serde_json::from_str(&json).unwrap();
+        assert_eq!(buf.capacity(), 4);
+        assert!(buf.is_overwrite());
+        assert_eq!(buf.as_slices(), (&[2, 3, 4][..], &[][..]));
+        buf.put_slice(&[5, 6]);
+        assert_eq!(buf.get_u32(), 0x0304_0506);
+    }
+
+    #[test]
+    fn ringbuffer_deserialize_bytes() {
+        let mut buf = RingBuffer::new(2);
+        buf.put_slice(&[1, 2]);
+        assert_de_tokens(
+            &Contents(buf),
+            &tokens(2, "Fixed", vec![Token::Bytes(&[1, 2])]),
+        );
+    }
+
+    #[test]
+    fn ringbuffer_deserialize_too_long() {
+        assert_de_tokens_error::<RingBuffer>(
+            &tokens(1, "Fixed", seq(&[1, 2])),
+            "invalid length 2, expected at most capacity bytes",
+        );
+        assert_de_tokens_error::<RingBuffer>(
+            &tokens(1, "Fixed", vec![Token::Bytes(&[1, 2])]),
+            "invalid length 2, expected at most capacity bytes",
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Ringbuffer that hands out reference-counted views of its readable bytes
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec;
+use core::mem::MaybeUninit;
+use core::{ptr, slice};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Heap memory shared between a `SharedRingBuffer` and its views
+#[derive(Debug)]
+struct Memory {
+    ptr: *mut MaybeUninit<u8>,
+    capacity: usize,
+}
+
+// Safe because the ringbuffer only writes to bytes that are not part of a view
+// and views never write.
+unsafe impl Send for Memory {}
+unsafe impl Sync for Memory {}
+
+impl Memory {
+    fn new(capacity: usize) -> Self {
+        let buffer = vec![MaybeUninit::<u8>::uninit(); capacity].into_boxed_slice();
+        Self {
+            ptr: Box::into_raw(buffer) as *mut MaybeUninit<u8>,
+            capacity,
+        }
+    }
+
+    /// Initialized bytes `start..start + len`
+    ///
+    /// The caller has to make sure that the bytes are initialized and not
+    /// written while the slice is alive.
+    unsafe fn slice(&self, start: usize, len: usize) -> &[u8] {
+        slice::from_raw_parts(self.ptr.add(start) as *const u8, len)
+    }
+
+    /// Bytes `start..start + len` for writing
+    ///
+    /// The caller has to make sure that nobody else accesses the bytes while the
+    /// slice is alive.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn slice_mut(&self, start: usize, len: usize) -> &mut [MaybeUninit<u8>] {
+        slice::from_raw_parts_mut(self.ptr.add(start), len)
+    }
+}
+
+impl Drop for Memory {
+    fn drop(&mut self) {
+        // Safe because `ptr` and `capacity` come from a boxed slice in `Memory::new`.
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.ptr,
+                self.capacity,
+            )));
+        }
+    }
+}
+
+/// Fixed-capacity ringbuffer whose readable bytes can be split off into
+/// reference-counted [View](struct.View.html)s without copying
+///
+/// The bytes of a view are not overwritten until the view and all of its
+/// clones are dropped. Until then they count against the capacity, even if
+/// later bytes have already been consumed.
+#[derive(Debug)]
+pub struct SharedRingBuffer {
+    memory: Arc<Memory>,
+    /// Position of the first byte that is held back or readable
+    begin: usize,
+    /// Number of consumed bytes in front of the readable bytes
+    held: usize,
+    len: usize,
+    /// Consumed regions in order. Regions without a token or whose token is
+    /// unique can be reused.
+    holds: VecDeque<(Option<Arc<()>>, usize)>,
+}
+
+/// Reference-counted view of bytes split off a `SharedRingBuffer`
+#[derive(Debug, Clone)]
+pub struct View {
+    memory: Arc<Memory>,
+    start: usize,
+    len: usize,
+    _token: Arc<()>,
+}
+
+impl SharedRingBuffer {
+    /// Create a ringbuffer with the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            memory: Arc::new(Memory::new(capacity)),
+            begin: 0,
+            held: 0,
+            len: 0,
+            holds: VecDeque::new(),
+        }
+    }
+
+    /// Capacity of the ringbuffer
+    pub fn capacity(&self) -> usize {
+        self.memory.capacity
+    }
+
+    /// Number of consumed bytes that cannot be reused yet because a view of
+    /// them is still alive
+    pub fn held(&self) -> usize {
+        self.held - self.releasable()
+    }
+
+    /// Remove the first `n` readable bytes and return a view of them
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `n` bytes are readable.
+    pub fn split_to_view(&mut self, n: usize) -> View {
+        assert!(n <= self.len);
+        let token = Arc::new(());
+        let view = View {
+            memory: self.memory.clone(),
+            start: self.wrap(self.begin + self.held),
+            len: n,
+            _token: token.clone(),
+        };
+        self.holds.push_back((Some(token), n));
+        self.held += n;
+        self.len -= n;
+        view
+    }
+
+    /// Wrap a position in `0..2 * capacity` into the storage
+    fn wrap(&self, pos: usize) -> usize {
+        if pos >= self.capacity() {
+            pos - self.capacity()
+        } else {
+            pos
+        }
+    }
+
+    /// Number of held bytes that can be reused because their views are gone
+    fn releasable(&self) -> usize {
+        self.holds
+            .iter()
+            .take_while(|(token, _)| token.as_ref().is_none_or(|t| Arc::strong_count(t) == 1))
+            .map(|(_, len)| len)
+            .sum()
+    }
+
+    /// Make the held bytes whose views are gone available for writing
+    fn release(&mut self) {
+        while let Some((token, len)) = self.holds.front_mut() {
+            // `Arc::get_mut` synchronizes with the drop of the last view.
+            if let Some(token) = token {
+                if Arc::get_mut(token).is_none() {
+                    break;
+                }
+            }
+            let len = *len;
+            self.begin = self.wrap(self.begin + len);
+            self.held -= len;
+            self.holds.pop_front();
+        }
+    }
+}
+
+impl View {
+    /// Number of bytes in the view
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the view is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Both segments of the bytes, in order
+    ///
+    /// The second segment is non-empty only if the view wraps around the end of
+    /// the storage.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let first = self.len.min(self.memory.capacity - self.start);
+        // Safe because the bytes were readable when the view was created and
+        // are not written until it is dropped.
+        unsafe {
+            (
+                self.memory.slice(self.start, first),
+                self.memory.slice(0, self.len - first),
+            )
+        }
+    }
+
+    /// Copy the bytes into a `Bytes`
+    pub fn to_bytes(&self) -> Bytes {
+        let (first, second) = self.as_slices();
+        let mut bytes = BytesMut::with_capacity(self.len);
+        bytes.extend_from_slice(first);
+        bytes.extend_from_slice(second);
+        bytes.freeze()
+    }
+}
+
+impl Buf for SharedRingBuffer {
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&self) -> &[u8] {
+        let begin = self.wrap(self.begin + self.held);
+        let cnt = self.len.min(self.capacity() - begin);
+        // Safe because the readable bytes have been declared initialized by
+        // `BufMut::advance_mut` and are not part of a view.
+        unsafe { self.memory.slice(begin, cnt) }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.len);
+        if self.holds.is_empty() {
+            self.begin = self.wrap(self.begin + cnt);
+        } else {
+            // The bytes can only be reused after the views in front of them
+            self.holds.push_back((None, cnt));
+            self.held += cnt;
+            self.release();
+        }
+        self.len -= cnt;
+    }
+}
+
+impl BufMut for SharedRingBuffer {
+    fn remaining_mut(&self) -> usize {
+        self.capacity() - self.held() - self.len
+    }
+
+    fn bytes_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        self.release();
+        let begin = self.wrap(self.begin + self.held + self.len);
+        let cnt = (self.capacity() - self.held - self.len).min(self.capacity() - begin);
+        // Safe because the free space is neither readable nor part of a view.
+        unsafe { self.memory.slice_mut(begin, cnt) }
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.release();
+        assert!(cnt <= self.capacity() - self.held - self.len);
+        self.len += cnt;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn shared_ringbuffer_views() {
+        let mut buf = SharedRingBuffer::new(8);
+        buf.put_slice(&[0, 1, 2, 3, 4, 5, 6, 7]);
+        let first = buf.split_to_view(2);
+        let second = buf.split_to_view(2);
+        assert_eq!(first.as_slices(), (&[0, 1][..], &[][..]));
+        assert_eq!(second.as_slices(), (&[2, 3][..], &[][..]));
+        assert_eq!(buf.held(), 4);
+        assert_eq!(buf.remaining_mut(), 0);
+
+        // Bytes behind a view are held back as well
+        buf.advance(2);
+        assert_eq!(buf.held(), 6);
+
+        // The second view is dropped first, but the first one still holds
+        drop(second);
+        assert_eq!(buf.held(), 6);
+        let clone = first.clone();
+        drop(first);
+        assert_eq!(buf.remaining_mut(), 0);
+        drop(clone);
+        assert_eq!(buf.held(), 0);
+        assert_eq!(buf.remaining_mut(), 6);
+
+        // A view can wrap around the end of the storage
+        buf.put_slice(&[8, 9, 10]);
+        buf.advance(1);
+        let view = buf.split_to_view(4);
+        assert_eq!(view.as_slices(), (&[7][..], &[8, 9, 10][..]));
+        assert_eq!(&view.to_bytes()[..], &[7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn shared_ringbuffer_threads() {
+        let mut buf = SharedRingBuffer::new(16);
+        let mut views = Vec::new();
+        for i in 0..4u32 {
+            buf.put_u32(i);
+            views.push(buf.split_to_view(4));
+        }
+        let reader = thread::spawn(move || {
+            for (i, view) in views.into_iter().enumerate() {
+                assert_eq!(view.to_bytes().get_u32(), i as u32);
+            }
+        });
+        reader.join().unwrap();
+        assert_eq!(buf.remaining_mut(), 16);
+        buf.put_slice(&[0; 16]);
+    }
+}
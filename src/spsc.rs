@@ -0,0 +1,217 @@
+//! Lock-free single-producer/single-consumer halves of a `RingBuffer`
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{ptr, slice};
+
+use super::{Buf, BufMut, RingBuffer};
+
+/// Storage shared between a `Producer` and a `Consumer`
+///
+/// `head` and `tail` are positions in `0..2 * capacity` so that a full buffer
+/// can be distinguished from an empty one. Only the producer stores to `tail`
+/// and only the consumer stores to `head`.
+#[derive(Debug)]
+struct Shared {
+    ptr: *mut MaybeUninit<u8>,
+    capacity: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Safe because the producer and the consumer never access the same bytes at the
+// same time. Ownership of the bytes is handed over through `head` and `tail`.
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+impl Shared {
+    fn len(&self, head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + 2 * self.capacity - head
+        }
+    }
+
+    fn index(&self, pos: usize) -> usize {
+        if pos >= self.capacity {
+            pos - self.capacity
+        } else {
+            pos
+        }
+    }
+
+    fn add(&self, pos: usize, cnt: usize) -> usize {
+        let pos = pos + cnt;
+        if pos >= 2 * self.capacity {
+            pos - 2 * self.capacity
+        } else {
+            pos
+        }
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        // Safe because `ptr` and `capacity` come from a boxed slice in `RingBuffer::split`.
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.ptr,
+                self.capacity,
+            )));
+        }
+    }
+}
+
+/// Writing half of a split `RingBuffer`
+#[derive(Debug)]
+pub struct Producer {
+    shared: Arc<Shared>,
+}
+
+/// Reading half of a split `RingBuffer`
+#[derive(Debug)]
+pub struct Consumer {
+    shared: Arc<Shared>,
+}
+
+impl RingBuffer {
+    /// Split the ringbuffer into a producer and a consumer that can be used from
+    /// different threads
+    ///
+    /// Bytes that have not been read yet are handed to the consumer. The halves
+    /// never overwrite unread bytes or grow, even if the buffer was created by
+    /// [with_overwrite](#method.with_overwrite) or [growable](#method.growable).
+    pub fn split(mut self) -> (Producer, Consumer) {
+        self.unpad();
+        let RingBuffer {
+            buffer, begin, len, ..
+        } = self;
+        let capacity = buffer.len();
+        let ptr = Box::into_raw(buffer.into_boxed_slice()) as *mut MaybeUninit<u8>;
+        let shared = Arc::new(Shared {
+            ptr,
+            capacity,
+            head: AtomicUsize::new(begin),
+            tail: AtomicUsize::new(begin + len),
+        });
+        (
+            Producer {
+                shared: shared.clone(),
+            },
+            Consumer { shared },
+        )
+    }
+}
+
+impl Producer {
+    /// Capacity of the ringbuffer
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+impl Consumer {
+    /// Capacity of the ringbuffer
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+impl BufMut for Producer {
+    fn remaining_mut(&self) -> usize {
+        let head = self.shared.head.load(Ordering::Acquire);
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        self.shared.capacity - self.shared.len(head, tail)
+    }
+
+    fn bytes_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        let begin = self.shared.index(tail);
+        let cnt = self.remaining_mut().min(self.shared.capacity - begin);
+        // Safe because the consumer does not access the free space until it is
+        // published by `advance_mut`.
+        unsafe { slice::from_raw_parts_mut(self.shared.ptr.add(begin), cnt) }
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining_mut());
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        self.shared
+            .tail
+            .store(self.shared.add(tail, cnt), Ordering::Release);
+    }
+}
+
+impl Buf for Consumer {
+    fn remaining(&self) -> usize {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        self.shared.len(head, tail)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        let begin = self.shared.index(head);
+        let cnt = self.remaining().min(self.shared.capacity - begin);
+        // Safe because the bytes have been initialized and published by the
+        // producer, which does not access them until they are released by `advance`.
+        unsafe { slice::from_raw_parts(self.shared.ptr.add(begin) as *const u8, cnt) }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining());
+        let head = self.shared.head.load(Ordering::Relaxed);
+        self.shared
+            .head
+            .store(self.shared.add(head, cnt), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ringbuffer_split() {
+        let mut buf = RingBuffer::new(4);
+        buf.put_slice(&[0, 1, 2]);
+        buf.advance(2);
+        buf.put_slice(&[3, 4]);
+
+        let (mut producer, mut consumer) = buf.split();
+        assert_eq!(consumer.remaining(), 3);
+        assert_eq!(producer.remaining_mut(), 1);
+        producer.put_u8(5);
+        assert_eq!(producer.remaining_mut(), 0);
+        for i in 2..6 {
+            assert_eq!(consumer.get_u8(), i);
+        }
+        assert_eq!(consumer.remaining(), 0);
+        assert_eq!(producer.remaining_mut(), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ringbuffer_split_threads() {
+        use std::thread;
+
+        let (mut producer, mut consumer) = RingBuffer::new(7).split();
+        let writer = thread::spawn(move || {
+            for i in 0..100_000u32 {
+                while producer.remaining_mut() < 4 {
+                    thread::yield_now();
+                }
+                producer.put_u32(i);
+            }
+        });
+        for i in 0..100_000u32 {
+            while consumer.remaining() < 4 {
+                thread::yield_now();
+            }
+            assert_eq!(consumer.get_u32(), i);
+        }
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+//! Memory backing a `RingBuffer`
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+use core::mem::MaybeUninit;
+
+/// Memory that can be used as the storage of a [RingBuffer](struct.RingBuffer.html)
+///
+/// # Safety
+///
+/// `as_slice` and `as_mut_slice` must always return the same memory, unless it
+/// has been replaced by `grow`. A ringbuffer relies on the bytes it has written
+/// still being there when it reads them.
+pub unsafe trait Storage {
+    /// The memory as a slice
+    fn as_slice(&self) -> &[MaybeUninit<u8>];
+
+    /// The memory as a mutable slice
+    fn as_mut_slice(&mut self) -> &mut [MaybeUninit<u8>];
+
+    /// Grow the memory to `capacity` bytes while keeping its contents
+    ///
+    /// Returns `false` if the memory cannot grow, which is the default, or if
+    /// allocating fails.
+    fn grow(&mut self, capacity: usize) -> bool {
+        let _ = capacity;
+        false
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl Storage for Vec<MaybeUninit<u8>> {
+    fn as_slice(&self) -> &[MaybeUninit<u8>] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        self
+    }
+
+    #[inline]
+    fn grow(&mut self, capacity: usize) -> bool {
+        let len = self.len();
+        if capacity > len {
+            if self.try_reserve_exact(capacity - len).is_err() {
+                return false;
+            }
+            // Safe because `MaybeUninit` does not need to be initialized.
+            unsafe { self.set_len(capacity) };
+        }
+        true
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl Storage for Box<[MaybeUninit<u8>]> {
+    fn as_slice(&self) -> &[MaybeUninit<u8>] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        self
+    }
+}
+
+unsafe impl Storage for &mut [MaybeUninit<u8>] {
+    fn as_slice(&self) -> &[MaybeUninit<u8>] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        self
+    }
+}
+
+unsafe impl<const N: usize> Storage for [MaybeUninit<u8>; N] {
+    fn as_slice(&self) -> &[MaybeUninit<u8>] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Buf, BufMut, RingBuffer};
+    use core::mem::MaybeUninit;
+
+    #[test]
+    fn ringbuffer_from_slice() {
+        let mut memory = [MaybeUninit::uninit(); 8];
+        let mut buf = RingBuffer::from_slice(&mut memory[..4]);
+        assert_eq!(buf.capacity(), 4);
+        buf.put_u16(1234);
+        buf.put_u16(5671);
+        assert_eq!(buf.remaining_mut(), 0);
+        assert_eq!(buf.get_u16(), 1234);
+        assert_eq!(buf.get_u16(), 5671);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ringbuffer_boxed_storage() {
+        let memory = alloc::vec![MaybeUninit::uninit(); 4].into_boxed_slice();
+        let mut buf = RingBuffer::from_storage(memory);
+        buf.put_slice(&[0, 1, 2]);
+        buf.advance(2);
+        buf.put_slice(&[3, 4, 5]);
+        assert_eq!(buf.as_slices(), (&[2, 3][..], &[4, 5][..]));
+
+        // Hand the memory back, e.g. to a pool
+        let memory = buf.into_storage();
+        assert_eq!(memory.len(), 4);
+    }
+}
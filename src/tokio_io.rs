@@ -0,0 +1,106 @@
+//! Helpers for moving data between a `RingBuffer` and tokio's async I/O traits
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::{Buf, BufMut, RingBuffer, Storage};
+
+impl<S: Storage> RingBuffer<S> {
+    /// Read from `reader` into the free space of the buffer
+    ///
+    /// If the free space wraps around the end of the storage both segments are
+    /// filled as long as `reader` does not return `Poll::Pending` in between.
+    /// Returns `Poll::Ready(Ok(0))` without polling `reader` if
+    /// `remaining_mut()` is zero.
+    pub fn poll_read_from<R: AsyncRead>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut reader: Pin<&mut R>,
+    ) -> Poll<io::Result<usize>> {
+        let contiguous = BufMut::bytes_mut(self).len();
+        let cnt = match reader.as_mut().poll_read_buf(cx, self) {
+            Poll::Ready(Ok(cnt)) => cnt,
+            other => return other,
+        };
+        if cnt < contiguous || !self.has_remaining_mut() {
+            return Poll::Ready(Ok(cnt));
+        }
+        // Errors are reported by the next call as some bytes have already been read.
+        match reader.poll_read_buf(cx, self) {
+            Poll::Ready(Ok(more)) => Poll::Ready(Ok(cnt + more)),
+            _ => Poll::Ready(Ok(cnt)),
+        }
+    }
+
+    /// Write the readable bytes of the buffer to `writer`
+    ///
+    /// If the readable bytes wrap around the end of the storage both segments are
+    /// written as long as `writer` does not return `Poll::Pending` in between.
+    /// Returns `Poll::Ready(Ok(0))` without polling `writer` if `remaining()` is
+    /// zero.
+    pub fn poll_write_to<W: AsyncWrite>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut writer: Pin<&mut W>,
+    ) -> Poll<io::Result<usize>> {
+        let contiguous = self.bytes().len();
+        let cnt = match writer.as_mut().poll_write_buf(cx, self) {
+            Poll::Ready(Ok(cnt)) => cnt,
+            other => return other,
+        };
+        if cnt < contiguous || !self.has_remaining() {
+            return Poll::Ready(Ok(cnt));
+        }
+        // Errors are reported by the next call as some bytes have already been written.
+        match writer.poll_write_buf(cx, self) {
+            Poll::Ready(Ok(more)) => Poll::Ready(Ok(cnt + more)),
+            _ => Poll::Ready(Ok(cnt)),
+        }
+    }
+
+    /// Read from `reader` into the free space of the buffer
+    ///
+    /// See [poll_read_from](#method.poll_read_from).
+    pub async fn read_from<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> io::Result<usize> {
+        poll_fn(|cx| self.poll_read_from(cx, Pin::new(&mut *reader))).await
+    }
+
+    /// Write the readable bytes of the buffer to `writer`
+    ///
+    /// See [poll_write_to](#method.poll_write_to).
+    pub async fn write_to<W: AsyncWrite + Unpin>(&mut self, writer: &mut W) -> io::Result<usize> {
+        poll_fn(|cx| self.poll_write_to(cx, Pin::new(&mut *writer))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrapped;
+
+    #[tokio::test]
+    async fn ringbuffer_tokio_read_from() {
+        // The free space wraps around the end of the storage
+        let mut buf = wrapped(RingBuffer::new(8), 3, &[]);
+        let mut reader: &[u8] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        assert_eq!(buf.read_from(&mut reader).await.unwrap(), 8);
+        assert_eq!(buf.read_from(&mut reader).await.unwrap(), 0);
+        assert_eq!(reader, &[8, 9]);
+        for i in 0..8 {
+            assert_eq!(buf.get_u8(), i);
+        }
+    }
+
+    #[tokio::test]
+    async fn ringbuffer_tokio_write_to() {
+        // The readable bytes wrap around the end of the storage
+        let mut buf = wrapped(RingBuffer::new(8), 3, &[0, 1, 2, 3, 4, 5]);
+        let mut writer = Vec::new();
+        assert_eq!(buf.write_to(&mut writer).await.unwrap(), 6);
+        assert_eq!(buf.write_to(&mut writer).await.unwrap(), 0);
+        assert_eq!(writer, &[0, 1, 2, 3, 4, 5]);
+    }
+}